@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Insufficient balance")]
+    InsufficientBalance,
+    #[msg("Transfer authority is not whitelisted")]
+    NotWhitelisted,
+    #[msg("Amount is too small to mint any shares")]
+    ZeroShares,
+    #[msg("Vault has no depositors to distribute yield to")]
+    NoDepositors,
+    #[msg("Lock duration must not be negative")]
+    InvalidLockDuration,
+    #[msg("Too many active deposit locks")]
+    TooManyLocks,
+    #[msg("Shares are locked until maturity")]
+    SharesLocked,
+    #[msg("Withdraw amount must be greater than zero")]
+    ZeroAmount,
+    #[msg("Withdraw request has not matured yet")]
+    WithdrawNotMatured,
+    #[msg("Withdraw cooldown must not be negative")]
+    InvalidCooldown,
+    #[msg("Fee exceeds 10000 basis points")]
+    InvalidFee,
+    #[msg("No fees to collect")]
+    NoFees,
+    #[msg("Vault holds no surplus over its liabilities")]
+    NoSurplus,
+    #[msg("Issuance would exceed the mint's maximum supply")]
+    SupplyCapExceeded,
+    #[msg("Clawback source must be a holder account other than the vault and destination")]
+    InvalidClawback,
+    #[msg("UI multiplier must be a positive finite number")]
+    InvalidMultiplier,
+    #[msg("Extra account metas must match the hook's account list")]
+    InvalidExtraAccountMetas,
+    #[msg("Trading window has invalid hours, weekdays or blackouts")]
+    InvalidTradingWindow,
+    #[msg("Transfers are not allowed at this time")]
+    TradingWindowClosed,
+    #[msg("Jurisdiction must be an ISO 3166-1 alpha-2 code")]
+    InvalidJurisdiction,
+    #[msg("Too many allowed jurisdiction pairs")]
+    TooManyJurisdictionPairs,
+    #[msg("Transfers between these jurisdictions are not allowed")]
+    JurisdictionNotAllowed,
+    #[msg("Transfer would exceed the destination's maximum holding")]
+    MaxHoldingExceeded,
+    #[msg("Beneficiary must come with the whitelist entry of that wallet")]
+    InvalidBeneficiary,
+    #[msg("Signer is not the vault admin")]
+    Unauthorized,
+    #[msg("Token account does not belong to the removed user")]
+    ForeignTokenAccount,
+    #[msg("Expected a token transfer next to this instruction")]
+    MissingTransferInstruction,
+    #[msg("Extra account meta list must be funded for rent before it is allocated")]
+    MetaListNotFunded,
+    #[msg("Holders with a maximum holding may only receive into their associated token account")]
+    HoldingOutsideAssociatedAccount,
+    #[msg("Whitelist entry is not suspended")]
+    UserNotSuspended,
+    #[msg("Only the vault mint may create token accounts frozen")]
+    DefaultFrozenSecondaryMint,
+}
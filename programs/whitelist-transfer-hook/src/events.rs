@@ -0,0 +1,254 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    instructions::{MetadataField, UiAmountConfig, ViolationReason},
+    state::PolicyRules,
+};
+
+#[event]
+pub struct VaultInitialized {
+    pub vault: Pubkey,
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub vault_token_account: Pubkey,
+}
+
+#[event]
+pub struct UserWhitelisted {
+    pub user: Pubkey,
+    pub jurisdiction: [u8; 2],
+}
+
+#[event]
+pub struct JurisdictionUpdated {
+    pub user: Pubkey,
+    pub jurisdiction: [u8; 2],
+}
+
+#[event]
+pub struct MaxHoldingUpdated {
+    pub user: Pubkey,
+    pub max_holding: Option<u64>,
+}
+
+#[event]
+pub struct UserRemoved {
+    pub user: Pubkey,
+    /// The entry was kept for the shares it still holds
+    pub suspended: bool,
+}
+
+#[event]
+pub struct Deposited {
+    pub user: Pubkey,
+    pub amount: u64,
+    /// Withheld by the mint's transfer-fee extension before the deposit reached the vault
+    pub transfer_fee: u64,
+    pub fee: u64,
+    pub shares_minted: u64,
+    pub shares: u64,
+    pub unlock_ts: i64,
+}
+
+#[event]
+pub struct WithdrawRequested {
+    pub user: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+    pub shares: u64,
+    pub unlock_ts: i64,
+}
+
+#[event]
+pub struct WithdrawProcessed {
+    pub user: Pubkey,
+    pub index: u64,
+    pub shares: u64,
+    pub payout: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub user: Pubkey,
+    pub index: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TransferAllowed {
+    pub mint: Pubkey,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+/// Log-only, emitted with `emit!` right before the hook fails the transfer. The transaction is
+/// rolled back, so the event is only found in simulation output or the logs of the failed
+/// transaction, never in its inner instructions. Use `PolicyViolation` in monitor mode to record
+/// would-be rejections in transactions that succeed.
+#[event]
+pub struct TransferRejected {
+    pub mint: Pubkey,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub reason: ViolationReason,
+}
+
+/// Emitted in monitor mode for a transfer that enforce mode would reject
+#[event]
+pub struct PolicyViolation {
+    pub mint: Pubkey,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub reason: ViolationReason,
+}
+
+#[event]
+pub struct MintCreated {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub decimals: u8,
+    pub max_supply: u64,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub default_frozen: bool,
+    pub pausable: bool,
+    pub transfer_fee_bps: Option<u16>,
+    pub ui_amount: Option<UiAmountConfig>,
+}
+
+#[event]
+pub struct TokensMinted {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub issued: u64,
+    pub max_supply: u64,
+}
+
+#[event]
+pub struct MintPaused {
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct MintResumed {
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct TransferFeesHarvested {
+    pub mint: Pubkey,
+    /// Number of token accounts harvested
+    pub accounts: u32,
+}
+
+#[event]
+pub struct TransferFeesWithdrawn {
+    pub mint: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct InterestRateUpdated {
+    pub mint: Pubkey,
+    /// Basis points per year
+    pub rate: i16,
+}
+
+#[event]
+pub struct UiMultiplierUpdated {
+    pub mint: Pubkey,
+    pub multiplier: f64,
+    pub effective_timestamp: i64,
+}
+
+#[event]
+pub struct MintPolicyUpdated {
+    pub mint: Pubkey,
+    pub rules: PolicyRules,
+}
+
+#[event]
+pub struct TokensClawedBack {
+    pub mint: Pubkey,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub amount: u64,
+    /// Issuer-defined code recording why the tokens were seized
+    pub reason: u16,
+}
+
+#[event]
+pub struct VaultReconciled {
+    pub vault_amount: u64,
+    pub liabilities: u64,
+    pub surplus: u64,
+    pub deficit: u64,
+}
+
+#[event]
+pub struct YieldDistributed {
+    pub amount: u64,
+    pub total_outstanding: u64,
+    pub total_shares: u64,
+}
+
+#[event]
+pub struct WithdrawCooldownUpdated {
+    pub withdraw_cooldown: i64,
+}
+
+#[event]
+pub struct FeesUpdated {
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+}
+
+#[event]
+pub struct TreasuryUpdated {
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct FeesCollected {
+    pub treasury: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SurplusSwept {
+    pub amount: u64,
+    /// Wallet credited with the surplus
+    pub beneficiary: Option<Pubkey>,
+    pub shares: u64,
+    pub treasury: Option<Pubkey>,
+}
+
+#[event]
+pub struct MetadataFieldUpdated {
+    pub mint: Pubkey,
+    pub field: MetadataField,
+    pub value: String,
+}
+
+#[event]
+pub struct MetadataKeyRemoved {
+    pub mint: Pubkey,
+    pub key: String,
+}
+
+#[event]
+pub struct MetadataAuthorityUpdated {
+    pub mint: Pubkey,
+    /// `None` once the authority is renounced
+    pub new_authority: Option<Pubkey>,
+}
@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{
+        burn, freeze_account, mint_to, thaw_account, Burn, FreezeAccount, Mint, MintTo,
+        ThawAccount, TokenAccount, TokenInterface,
+    },
+};
+
+use crate::{
+    error::ErrorCode,
+    events::TokensClawedBack,
+    state::{MintPolicy, TransferLog, TransferRecord, User, Vault},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Clawback<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        mint::authority = vault,
+        mint::token_program = token_program,
+        extensions::permanent_delegate::delegate = vault,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// Seized tokens may only land with a whitelisted wallet
+    #[account(
+        seeds = [b"user", destination.owner.as_ref()],
+        bump = destination_state.bump,
+        constraint = !destination_state.suspended @ ErrorCode::NotWhitelisted,
+    )]
+    pub destination_state: Account<'info, User>,
+
+    /// CHECK: Mint policy PDA, may not exist, then the default rules apply
+    #[account(
+        seeds = [b"mint-policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    /// The hook never sees a clawback, so the seizure is recorded here instead
+    #[account(
+        mut,
+        seeds = [b"transfer-log", mint.key().as_ref()],
+        bump = transfer_log.load()?.bump,
+    )]
+    pub transfer_log: AccountLoader<'info, TransferLog>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Clawback<'info> {
+    /// Moves `amount` out of any holder's account. A `transfer_checked` would re-enter this
+    /// program through the hook, so the vault burns as permanent delegate and mints the same
+    /// amount to the destination, leaving supply and the issuance cap untouched.
+    ///
+    /// Neither burning nor minting invokes the hook, so the mint policy's jurisdictions and
+    /// trading window are not applied: the destination only has to be whitelisted. Its holding
+    /// limit is checked against the resulting balance, and the seizure is appended to the
+    /// transfer log explicitly. A paused mint rejects both burning and minting, so the mint has
+    /// to be resumed first.
+    pub fn clawback(&mut self, amount: u64, reason: u16) -> Result<TokensClawedBack> {
+        require_gt!(amount, 0, ErrorCode::ZeroAmount);
+        require_keys_neq!(
+            self.source.key(),
+            self.vault.vault_token_account,
+            ErrorCode::InvalidClawback
+        );
+        require_keys_neq!(
+            self.source.key(),
+            self.destination.key(),
+            ErrorCode::InvalidClawback
+        );
+        require_gte!(self.source.amount, amount, ErrorCode::InsufficientBalance);
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        // Removed holders are frozen, which blocks burning until the seizure is done
+        let frozen = self.source.is_frozen();
+        if frozen {
+            thaw_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                ThawAccount {
+                    account: self.source.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        burn(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.mint.to_account_info(),
+                    from: self.source.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        if frozen {
+            freeze_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                FreezeAccount {
+                    account: self.source.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                MintTo {
+                    mint: self.mint.to_account_info(),
+                    to: self.destination.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        self.destination.reload()?;
+        let associated = self.destination.key()
+            == get_associated_token_address_with_program_id(
+                &self.destination.owner,
+                &self.mint.key(),
+                &self.token_program.key(),
+            );
+        MintPolicy::load_rules(&self.mint_policy)?.check_holding(
+            &self.destination_state,
+            associated,
+            self.destination.amount,
+        )?;
+
+        self.transfer_log.load_mut()?.append(TransferRecord {
+            slot: Clock::get()?.slot,
+            source_owner: self.source.owner,
+            destination_owner: self.destination.owner,
+            amount,
+        });
+
+        Ok(TokensClawedBack {
+            mint: self.mint.key(),
+            source_owner: self.source.owner,
+            destination_owner: self.destination.owner,
+            amount,
+            reason,
+        })
+    }
+}
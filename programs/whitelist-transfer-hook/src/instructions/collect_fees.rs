@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{approve, Approve, TokenAccount, TokenInterface};
+
+use crate::{
+    error::ErrorCode, events::FeesCollected, instructions::check_transfer_instruction, state::Vault,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+        has_one = vault_token_account,
+        has_one = treasury,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: instructions sysvar account
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CollectFees<'info> {
+    /// Approves the admin to move the accrued fees to the treasury in the next instruction.
+    /// The transfer runs through the hook, so the admin must be whitelisted.
+    pub fn collect_fees(&mut self) -> Result<FeesCollected> {
+        let amount = self.vault.accrued_fees;
+        require_gt!(amount, 0, ErrorCode::NoFees);
+
+        let transferred = check_transfer_instruction(
+            &self.instructions,
+            1,
+            Some(self.vault_token_account.key()),
+            Some(self.treasury.key()),
+            self.admin.key(),
+        )?;
+        require_eq!(transferred, amount);
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        approve(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Approve {
+                    to: self.vault_token_account.to_account_info(),
+                    delegate: self.admin.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        self.vault.accrued_fees = 0;
+
+        Ok(FeesCollected {
+            treasury: self.treasury.key(),
+            amount,
+        })
+    }
+}
@@ -1,16 +1,17 @@
-use anchor_lang::{
-    prelude::*,
-    solana_program::sysvar::instructions::{
-        load_current_index_checked, load_instruction_at_checked,
-    },
-};
+use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::state::{User, Vault};
+use crate::{
+    error::ErrorCode,
+    events::Deposited,
+    instructions::{check_transfer_instruction, transfer_fee_for},
+    state::{Lock, User, Vault, MAX_LOCKS},
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Deposit<'info> {
     #[account(mut)]
@@ -62,34 +63,92 @@ pub struct Deposit<'info> {
 }
 
 impl<'info> Deposit<'info> {
-    pub fn deposit(&mut self) -> Result<()> {
-        let amount = self.check_transfer_instruction()?;
-
-        match self.user_state.balance.checked_add(amount) {
-            Some(x) => self.user_state.balance = x,
-            None => {} // add error
-        }
-
-        Ok(())
-    }
-
-    pub fn check_transfer_instruction(&self) -> Result<u64> {
-        // instruction introspection
-        let current_index =
-            load_current_index_checked(&self.instructions.to_account_info())? as usize;
-        let ix =
-            load_instruction_at_checked(current_index - 1, &self.instructions.to_account_info())?;
-
-        // check above ix is from token22 program and transferchecked
-        require_keys_eq!(ix.program_id, anchor_spl::token_2022::ID);
-        require_eq!(ix.data.split_first().unwrap().0, &12);
-
-        let authority = ix.accounts.get(4).unwrap();
-        require_keys_eq!(authority.pubkey, self.user.key());
-
-        let amount_bytes = &ix.data[1..9];
-        let amount = u64::from_le_bytes(amount_bytes.try_into().unwrap());
-
-        Ok(amount)
+    pub fn deposit(&mut self, lock_duration: i64) -> Result<Deposited> {
+        require_gte!(lock_duration, 0, ErrorCode::InvalidLockDuration);
+
+        // tokens must land in the vault, otherwise the ledger drifts from the vault balance
+        let amount = check_transfer_instruction(
+            &self.instructions,
+            -1,
+            None,
+            Some(self.vault_ata.key()),
+            self.user.key(),
+        )?;
+
+        // Token-2022 withholds the transfer fee in the vault token account, only the rest arrives
+        let transfer_fee = transfer_fee_for(&self.mint.to_account_info(), amount)?;
+        let received = amount
+            .checked_sub(transfer_fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let fee = self
+            .vault
+            .deposit_fee(received)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let credited = received.checked_sub(fee).ok_or(ErrorCode::MathOverflow)?;
+
+        let shares = self
+            .vault
+            .shares_for_deposit(credited)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require_gt!(shares, 0, ErrorCode::ZeroShares);
+
+        self.user_state.shares = self
+            .user_state
+            .shares
+            .checked_add(shares)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        self.user_state.prune_locks(now);
+
+        let unlock_ts = if lock_duration > 0 {
+            require_gt!(
+                MAX_LOCKS,
+                self.user_state.locks.len(),
+                ErrorCode::TooManyLocks
+            );
+
+            let unlock_ts = now
+                .checked_add(lock_duration)
+                .ok_or(ErrorCode::MathOverflow)?;
+            self.user_state.locks.push(Lock { shares, unlock_ts });
+
+            unlock_ts
+        } else {
+            now
+        };
+
+        self.vault.total_shares = self
+            .vault
+            .total_shares
+            .checked_add(shares)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        self.vault.total_deposited = self
+            .vault
+            .total_deposited
+            .checked_add(received)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.vault.total_outstanding = self
+            .vault
+            .total_outstanding
+            .checked_add(credited)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.vault.accrued_fees = self
+            .vault
+            .accrued_fees
+            .checked_add(fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(Deposited {
+            user: self.user.key(),
+            amount,
+            transfer_fee,
+            fee,
+            shares_minted: shares,
+            shares: self.user_state.shares,
+            unlock_ts,
+        })
     }
 }
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    error::ErrorCode,
+    events::YieldDistributed,
+    instructions::{check_transfer_instruction, transfer_fee_for},
+    state::Vault,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DistributeYield<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+        has_one = mint,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: instructions sysvar account
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+impl<'info> DistributeYield<'info> {
+    /// Adds the tokens transferred in the previous instruction to the vault without minting
+    /// shares, which raises the price per share for every depositor pro rata.
+    pub fn distribute_yield(&mut self) -> Result<YieldDistributed> {
+        require_gt!(self.vault.total_shares, 0, ErrorCode::NoDepositors);
+
+        let transferred = check_transfer_instruction(
+            &self.instructions,
+            -1,
+            None,
+            Some(self.vault_token_account.key()),
+            self.admin.key(),
+        )?;
+        let transfer_fee = transfer_fee_for(&self.mint.to_account_info(), transferred)?;
+        let amount = transferred
+            .checked_sub(transfer_fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        self.vault.total_outstanding = self
+            .vault
+            .total_outstanding
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(YieldDistributed {
+            amount,
+            total_outstanding: self.vault.total_outstanding,
+            total_shares: self.vault.total_shares,
+        })
+    }
+}
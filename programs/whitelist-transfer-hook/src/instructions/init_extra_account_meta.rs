@@ -1,38 +1,83 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program::invoke_signed, system_instruction},
+    system_program::{transfer, Transfer},
+};
 use anchor_spl::token_interface::Mint;
 use spl_tlv_account_resolution::{
     account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
 };
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+use crate::{error::ErrorCode, state::Vault, ID};
+
+/// Borsh mirror of `ExtraAccountMeta`. A `Vec` of it encodes exactly like the length-prefixed
+/// list in the interface's initialize and update instruction data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ExtraAccountMetaData {
+    pub discriminator: u8,
+    pub address_config: [u8; 32],
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&ExtraAccountMeta> for ExtraAccountMetaData {
+    fn from(meta: &ExtraAccountMeta) -> Self {
+        Self {
+            discriminator: meta.discriminator,
+            address_config: meta.address_config,
+            is_signer: meta.is_signer.into(),
+            is_writable: meta.is_writable.into(),
+        }
+    }
+}
+
+/// The hook reads its extra accounts by position, so clients may only pass the canonical list,
+/// or nothing to have it filled in.
+pub fn resolve_extra_account_metas(
+    requested: &[ExtraAccountMetaData],
+) -> Result<Vec<ExtraAccountMeta>> {
+    let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas()?;
+
+    if !requested.is_empty() {
+        require!(
+            requested
+                .iter()
+                .copied()
+                .eq(extra_account_metas.iter().map(ExtraAccountMetaData::from)),
+            ErrorCode::InvalidExtraAccountMetas
+        );
+    }
 
-use crate::ID;
+    Ok(extra_account_metas)
+}
 
+/// Exactly the transfer-hook interface's `InitializeExtraAccountMetaList` accounts, so generic
+/// clients can initialize the list. The authority is read-only and cannot pay, so the list
+/// address has to be funded for rent beforehand. The list content is fixed by this program,
+/// which lets any signer initialize it.
 #[derive(Accounts)]
 pub struct InitializeExtraAccountMetaList<'info> {
-    #[account(mut)]
-    payer: Signer<'info>,
-
-    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds. Allocated here from its
+    /// pre-funded lamports.
     #[account(
-        init,
+        mut,
         seeds = [b"extra-account-metas", mint.key().as_ref()],
         bump,
-        space = ExtraAccountMetaList::size_of(
-            InitializeExtraAccountMetaList::extra_account_metas()?.len()
-        ).unwrap(),
-        payer = payer
+        owner = anchor_lang::system_program::ID,
     )]
-    pub extra_account_meta_list: AccountInfo<'info>,
+    pub extra_account_meta_list: UncheckedAccount<'info>,
     pub mint: InterfaceAccount<'info, Mint>,
+
+    pub authority: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> InitializeExtraAccountMetaList<'info> {
     pub fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
-        let (vault_pda, _bump) = Pubkey::find_program_address(&[b"vault"], &ID);
-        let vault_meta =
-            ExtraAccountMeta::new_with_pubkey(&vault_pda.to_bytes().into(), false, false).unwrap();
-
-        let user_meta = ExtraAccountMeta::new_with_seeds(
+        // whitelist entry of the transfer authority, owner or delegate
+        let authority_user_meta = ExtraAccountMeta::new_with_seeds(
             &[
                 Seed::Literal {
                     bytes: b"user".to_vec(),
@@ -44,6 +89,208 @@ impl<'info> InitializeExtraAccountMetaList<'info> {
         )
         .unwrap();
 
-        Ok(vec![vault_meta, user_meta])
+        // whitelist entries of the source and destination owners, read from the token accounts'
+        // owner field
+        let owner_user_meta = ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: b"user".to_vec(),
+                },
+                Seed::AccountData {
+                    account_index: 0,
+                    data_index: 32,
+                    length: 32,
+                },
+            ],
+            false,
+            false,
+        )
+        .unwrap();
+
+        let destination_user_meta = ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: b"user".to_vec(),
+                },
+                Seed::AccountData {
+                    account_index: 2,
+                    data_index: 32,
+                    length: 32,
+                },
+            ],
+            false,
+            false,
+        )
+        .unwrap();
+
+        let mint_policy_meta = ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: b"mint-policy".to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            false,
+        )
+        .unwrap();
+
+        let transfer_log_meta = ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: b"transfer-log".to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )
+        .unwrap();
+
+        // accounts required by emit_cpi! inside the hook
+        let (event_authority, _bump) = Pubkey::find_program_address(&[b"__event_authority"], &ID);
+        let event_authority_meta =
+            ExtraAccountMeta::new_with_pubkey(&event_authority.to_bytes().into(), false, false)
+                .unwrap();
+        let program_meta =
+            ExtraAccountMeta::new_with_pubkey(&ID.to_bytes().into(), false, false).unwrap();
+
+        Ok(vec![
+            authority_user_meta,
+            owner_user_meta,
+            destination_user_meta,
+            mint_policy_meta,
+            transfer_log_meta,
+            event_authority_meta,
+            program_meta,
+        ])
+    }
+
+    pub fn initialize_extra_account_meta_list(
+        &self,
+        extra_account_metas: &[ExtraAccountMeta],
+        bumps: &InitializeExtraAccountMetaListBumps,
+    ) -> Result<()> {
+        let meta_list = self.extra_account_meta_list.to_account_info();
+        let len = ExtraAccountMetaList::size_of(extra_account_metas.len())
+            .map_err(|_| ErrorCode::InvalidExtraAccountMetas)?;
+        require_gte!(
+            meta_list.lamports(),
+            Rent::get()?.minimum_balance(len),
+            ErrorCode::MetaListNotFunded
+        );
+
+        let mint = self.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"extra-account-metas",
+            mint.as_ref(),
+            &[bumps.extra_account_meta_list],
+        ]];
+
+        invoke_signed(
+            &system_instruction::allocate(meta_list.key, len as u64),
+            std::slice::from_ref(&meta_list),
+            signer_seeds,
+        )?;
+        invoke_signed(
+            &system_instruction::assign(meta_list.key, &ID),
+            std::slice::from_ref(&meta_list),
+            signer_seeds,
+        )?;
+
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut meta_list.try_borrow_mut_data()?,
+            extra_account_metas,
+        )
+        .map_err(|_| ErrorCode::InvalidExtraAccountMetas)?;
+
+        Ok(())
+    }
+}
+
+/// Starts with the transfer-hook interface's `UpdateExtraAccountMetaList` accounts, the
+/// authority marked writable, followed by the vault and the system program. Only the vault admin
+/// may rewrite the list.
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+        owner = ID,
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Pays for a larger list and receives the rent freed by a smaller one
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        constraint = vault.admin == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UpdateExtraAccountMetaList<'info> {
+    /// Rewrites the list with `extra_account_metas`, resizing the account and its rent to fit.
+    pub fn update_extra_account_meta_list(
+        &mut self,
+        extra_account_metas: &[ExtraAccountMeta],
+    ) -> Result<()> {
+        let meta_list = self.extra_account_meta_list.to_account_info();
+        let current_len = meta_list.data_len();
+        let new_len = ExtraAccountMetaList::size_of(extra_account_metas.len())
+            .map_err(|_| ErrorCode::InvalidExtraAccountMetas)?;
+
+        // The TLV entry is rewritten in place, so grow before and shrink after the update
+        if new_len > current_len {
+            self.adjust_rent(new_len)?;
+            meta_list.realloc(new_len, false)?;
+        }
+
+        ExtraAccountMetaList::update::<ExecuteInstruction>(
+            &mut meta_list.try_borrow_mut_data()?,
+            extra_account_metas,
+        )
+        .map_err(|_| ErrorCode::InvalidExtraAccountMetas)?;
+
+        if new_len <= current_len {
+            meta_list.realloc(new_len, false)?;
+            self.adjust_rent(new_len)?;
+        }
+
+        Ok(())
+    }
+
+    /// Tops the list up to rent exemption for `len` bytes from the admin, or refunds the excess.
+    fn adjust_rent(&self, len: usize) -> Result<()> {
+        let meta_list = self.extra_account_meta_list.to_account_info();
+        let required = Rent::get()?.minimum_balance(len);
+        let current = meta_list.lamports();
+
+        if required > current {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.authority.to_account_info(),
+                        to: meta_list,
+                    },
+                ),
+                required - current,
+            )?;
+        } else if current > required {
+            let excess = current - required;
+            **meta_list.try_borrow_mut_lamports()? -= excess;
+            **self.authority.to_account_info().try_borrow_mut_lamports()? += excess;
+        }
+
+        Ok(())
     }
 }
@@ -1,11 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{thaw_account, Mint, ThawAccount, TokenAccount, TokenInterface},
 };
 
-use crate::state::Vault;
+use crate::{events::VaultInitialized, state::Vault};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializeVault<'info> {
     #[account(mut)]
@@ -25,6 +26,7 @@ pub struct InitializeVault<'info> {
     )]
     pub mint: InterfaceAccount<'info, Mint>,
 
+    /// Thawed here when the mint creates accounts frozen
     #[account(
         init,
         payer = admin,
@@ -40,14 +42,44 @@ pub struct InitializeVault<'info> {
 }
 
 impl<'info> InitializeVault<'info> {
-    pub fn initialize_vault(&mut self, bumps: InitializeVaultBumps) -> Result<()> {
+    pub fn initialize_vault(&mut self, bumps: &InitializeVaultBumps) -> Result<VaultInitialized> {
         self.vault.set_inner(Vault {
             admin: self.admin.key(),
             mint: self.mint.key(),
             vault_token_account: self.vault_token_account.key(),
+            total_deposited: 0,
+            total_withdrawn: 0,
+            total_outstanding: 0,
+            total_shares: 0,
+            total_claimable: 0,
+            user_count: 0,
+            withdraw_cooldown: 0,
+            deposit_fee_bps: 0,
+            withdraw_fee_bps: 0,
+            treasury: Pubkey::default(),
+            accrued_fees: 0,
             bump: bumps.vault,
         });
 
-        Ok(())
+        if self.vault_token_account.is_frozen() {
+            let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[bumps.vault]]];
+
+            thaw_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                ThawAccount {
+                    account: self.vault_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        Ok(VaultInitialized {
+            vault: self.vault.key(),
+            admin: self.admin.key(),
+            mint: self.mint.key(),
+            vault_token_account: self.vault_token_account.key(),
+        })
     }
 }
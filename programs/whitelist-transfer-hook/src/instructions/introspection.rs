@@ -0,0 +1,51 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::instructions::{
+        load_current_index_checked, load_instruction_at_checked,
+    },
+};
+use spl_token_2022::instruction::TokenInstruction;
+
+use crate::error::ErrorCode;
+
+/// Amount moved by the Token-2022 `TransferChecked` found `offset` instructions away from the
+/// current one, once it is confirmed to move tokens out of `source` and into `destination`
+/// (each checked only when given) under `authority`. Anything else at that position, including
+/// nothing at all, is a [`ErrorCode::MissingTransferInstruction`].
+pub fn check_transfer_instruction(
+    instructions: &AccountInfo,
+    offset: i64,
+    source: Option<Pubkey>,
+    destination: Option<Pubkey>,
+    authority: Pubkey,
+) -> Result<u64> {
+    let current_index = load_current_index_checked(instructions)?;
+    let ix = i64::from(current_index)
+        .checked_add(offset)
+        .and_then(|index| usize::try_from(index).ok())
+        .and_then(|index| load_instruction_at_checked(index, instructions).ok())
+        .ok_or(ErrorCode::MissingTransferInstruction)?;
+
+    require_keys_eq!(
+        ix.program_id,
+        anchor_spl::token_2022::ID,
+        ErrorCode::MissingTransferInstruction
+    );
+    let Ok(TokenInstruction::TransferChecked { amount, .. }) = TokenInstruction::unpack(&ix.data)
+    else {
+        return err!(ErrorCode::MissingTransferInstruction);
+    };
+
+    // source, mint, destination, authority; the token program ties the mint to both accounts
+    let [ix_source, _, ix_destination, ix_authority, ..] = ix.accounts.as_slice() else {
+        return err!(ErrorCode::MissingTransferInstruction);
+    };
+    require!(
+        source.is_none_or(|source| source == ix_source.pubkey)
+            && destination.is_none_or(|destination| destination == ix_destination.pubkey)
+            && ix_authority.pubkey == authority,
+        ErrorCode::MissingTransferInstruction
+    );
+
+    Ok(amount)
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    events::MintPolicyUpdated,
+    state::{MintPolicy, PolicyRules, Vault},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeMintPolicy<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// Any mint issued by the vault, not only the one it holds deposits in
+    #[account(mint::authority = vault)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = MintPolicy::DISCRIMINATOR.len() + MintPolicy::INIT_SPACE,
+        seeds = [b"mint-policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: Account<'info, MintPolicy>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeMintPolicy<'info> {
+    pub fn initialize_mint_policy(
+        &mut self,
+        rules: PolicyRules,
+        bumps: &InitializeMintPolicyBumps,
+    ) -> Result<MintPolicyUpdated> {
+        rules.validate()?;
+
+        self.mint_policy.set_inner(MintPolicy {
+            mint: self.mint.key(),
+            rules: rules.clone(),
+            bump: bumps.mint_policy,
+        });
+
+        Ok(MintPolicyUpdated {
+            mint: self.mint.key(),
+            rules,
+        })
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateMintPolicy<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mint::authority = vault)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-policy", mint.key().as_ref()],
+        bump = mint_policy.bump,
+    )]
+    pub mint_policy: Account<'info, MintPolicy>,
+}
+
+impl<'info> UpdateMintPolicy<'info> {
+    /// Replaces every rule at once, takes effect from the next transfer.
+    pub fn update_mint_policy(&mut self, rules: PolicyRules) -> Result<MintPolicyUpdated> {
+        rules.validate()?;
+
+        self.mint_policy.rules = rules.clone();
+
+        Ok(MintPolicyUpdated {
+            mint: self.mint.key(),
+            rules,
+        })
+    }
+}
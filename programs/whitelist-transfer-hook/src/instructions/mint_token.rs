@@ -1,61 +1,167 @@
 use anchor_lang::{
     prelude::*,
-    solana_program::rent::{DEFAULT_EXEMPTION_THRESHOLD, DEFAULT_LAMPORTS_PER_BYTE_YEAR},
-    system_program::{transfer, Transfer},
+    solana_program::program::invoke,
+    system_program::{create_account, CreateAccount},
 };
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_2022::MintTo,
+    token_2022::{InitializeMint2, MintTo},
     token_interface::{
-        self, token_metadata_initialize, Mint, TokenAccount, TokenInterface,
-        TokenMetadataInitialize,
+        self, interest_bearing_mint_initialize, metadata_pointer_initialize,
+        permanent_delegate_initialize, token_metadata_initialize, transfer_fee_initialize,
+        transfer_hook_initialize, InterestBearingMintInitialize, MetadataPointerInitialize, Mint,
+        PermanentDelegateInitialize, TokenAccount, TokenInterface, TokenMetadataInitialize,
+        TransferFeeInitialize, TransferHookInitialize,
     },
 };
+use spl_token_2022::{
+    extension::{default_account_state, pausable, scaled_ui_amount, ExtensionType},
+    state::{AccountState, Mint as MintState},
+};
 use spl_token_metadata_interface::state::TokenMetadata;
 use spl_type_length_value::variable_len_pack::VariableLenPack;
 
+use crate::{
+    error::ErrorCode,
+    events::{MintCreated, TokensMinted},
+    instructions::UiAmountConfig,
+    state::{MintConfig, MintPolicy, TransferLog, User, Vault, MAX_FEE_BPS},
+};
+
+/// Token-2022 transfer fee levied on every transfer of the mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct TransferFeeArgs {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+}
+
+/// Optional Token-2022 extensions enabled by `create_mint` on top of the transfer hook, metadata
+/// and permanent delegate every mint gets.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct MintExtensions {
+    /// New token accounts start frozen and are only thawed once their owner is whitelisted.
+    /// Whitelisting thaws accounts of the vault mint alone, so only that mint may enable it.
+    pub default_frozen: bool,
+    /// Lets the vault halt transfers, mints and burns through `pause_mint`
+    pub pausable: bool,
+    /// Withheld in the destination account, harvested and withdrawn through the vault
+    pub transfer_fee: Option<TransferFeeArgs>,
+    /// Interest-bearing and scaled UI amounts cannot be combined on one mint
+    pub ui_amount: Option<UiAmountConfig>,
+}
+
+impl MintExtensions {
+    fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut types = vec![
+            ExtensionType::TransferHook,
+            ExtensionType::MetadataPointer,
+            ExtensionType::PermanentDelegate,
+        ];
+        if self.default_frozen {
+            types.push(ExtensionType::DefaultAccountState);
+        }
+        if self.pausable {
+            types.push(ExtensionType::Pausable);
+        }
+        if self.transfer_fee.is_some() {
+            types.push(ExtensionType::TransferFeeConfig);
+        }
+        match self.ui_amount {
+            Some(UiAmountConfig::InterestBearing { .. }) => {
+                types.push(ExtensionType::InterestBearingConfig)
+            }
+            Some(UiAmountConfig::ScaledUiAmount { .. }) => {
+                types.push(ExtensionType::ScaledUiAmount)
+            }
+            None => {}
+        }
+
+        types
+    }
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct TokenFactory<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
 
+    /// CHECK: vault PDA, holds every authority over the mint so they can only be exercised
+    /// through this program. The vault is a singleton initialized with a mint, so it does not
+    /// exist yet when its own mint is created. Once it does only its admin may create mints.
+    #[account(
+        seeds = [b"vault"],
+        bump,
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// Created here rather than through `init` since Anchor cannot set up every extension
     #[account(mut)]
-    pub user: SystemAccount<'info>,
+    pub mint: Signer<'info>,
 
     #[account(
-        init_if_needed,
+        init,
         payer = admin,
-        mint::decimals = 9,
-        mint::authority = admin,
-        extensions::transfer_hook::authority = admin,
-        extensions::transfer_hook::program_id = crate::ID,
-        extensions::metadata_pointer::authority = admin,
-        extensions::metadata_pointer::metadata_address = mint,
+        space = MintConfig::DISCRIMINATOR.len() + MintConfig::INIT_SPACE,
+        seeds = [b"mint-config", mint.key().as_ref()],
+        bump,
     )]
-    pub mint: InterfaceAccount<'info, Mint>,
+    pub mint_config: Account<'info, MintConfig>,
 
+    /// Appended to by the hook, created with the mint so that initializing the extra account
+    /// meta list through the interface is all a mint needs before its first transfer
     #[account(
-        init_if_needed,
+        init,
         payer = admin,
-        associated_token::mint = mint,
-        associated_token::authority = user,
-        associated_token::token_program = token_program
+        space = TransferLog::DISCRIMINATOR.len() + std::mem::size_of::<TransferLog>(),
+        seeds = [b"transfer-log", mint.key().as_ref()],
+        bump,
     )]
-    pub user_ata: InterfaceAccount<'info, TokenAccount>,
+    pub transfer_log: AccountLoader<'info, TransferLog>,
 
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> TokenFactory<'info> {
-    pub fn init_mint(
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_mint(
         &mut self,
+        decimals: u8,
+        max_supply: u64,
         name: String,
         symbol: String,
         uri: String,
-        amount: u64,
-    ) -> Result<()> {
+        extensions: MintExtensions,
+        bumps: &TokenFactoryBumps,
+    ) -> Result<MintCreated> {
+        self.check_admin()?;
+
+        // The vault mint is created before the vault it is then initialized with
+        require!(
+            !extensions.default_frozen || self.vault.owner != &crate::ID,
+            ErrorCode::DefaultFrozenSecondaryMint
+        );
+
+        if let Some(transfer_fee) = extensions.transfer_fee {
+            require_gte!(
+                MAX_FEE_BPS,
+                transfer_fee.basis_points,
+                ErrorCode::InvalidFee
+            );
+        }
+
+        self.mint_config.set_inner(MintConfig {
+            mint: self.mint.key(),
+            max_supply,
+            issued: 0,
+            bump: bumps.mint_config,
+        });
+
+        let mut transfer_log = self.transfer_log.load_init()?;
+        transfer_log.mint = self.mint.key();
+        transfer_log.bump = bumps.transfer_log;
+
         // https://github.com/solana-developers/program-examples/blob/main/tokens/token-2022/metadata/anchor/programs/metadata/src/instructions/initialize.rs
         let token_metadata = TokenMetadata {
             name: name.clone(),
@@ -64,51 +170,298 @@ impl<'info> TokenFactory<'info> {
             ..Default::default()
         };
 
-        // Add 4 extra bytes for size of MetadataExtension (2 bytes for type, 2 bytes for length)
-        let data_len = 4 + token_metadata.get_packed_len()?;
+        let space =
+            ExtensionType::try_calculate_account_len::<MintState>(&extensions.extension_types())?;
 
-        // Calculate lamports required for the additional metadata
-        let lamports =
-            data_len as u64 * DEFAULT_LAMPORTS_PER_BYTE_YEAR * DEFAULT_EXEMPTION_THRESHOLD as u64;
+        // Add 4 extra bytes for size of MetadataExtension (2 bytes for type, 2 bytes for length).
+        // Only `space` is allocated, token metadata reallocs the mint into the prepaid rent.
+        let metadata_len = 4 + token_metadata.get_packed_len()?;
+        let lamports = Rent::get()?.minimum_balance(space + metadata_len);
 
-        transfer(
+        create_account(
             CpiContext::new(
                 self.system_program.to_account_info(),
-                Transfer {
+                CreateAccount {
                     from: self.admin.to_account_info(),
                     to: self.mint.to_account_info(),
                 },
             ),
             lamports,
+            space as u64,
+            self.token_program.key,
         )?;
 
-        token_metadata_initialize(
+        self.initialize_extensions(&extensions)?;
+
+        token_interface::initialize_mint2(
             CpiContext::new(
+                self.token_program.to_account_info(),
+                InitializeMint2 {
+                    mint: self.mint.to_account_info(),
+                },
+            ),
+            decimals,
+            &self.vault.key(),
+            Some(&self.vault.key()),
+        )?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[bumps.vault]]];
+
+        token_metadata_initialize(
+            CpiContext::new_with_signer(
                 self.token_program.to_account_info(),
                 TokenMetadataInitialize {
                     mint: self.mint.to_account_info(),
                     metadata: self.mint.to_account_info(), // points to self
-                    mint_authority: self.admin.to_account_info(),
-                    update_authority: self.admin.to_account_info(),
+                    mint_authority: self.vault.to_account_info(),
+                    update_authority: self.vault.to_account_info(),
                     program_id: self.token_program.to_account_info(),
                 },
+                signer_seeds,
             ),
+            name.clone(),
+            symbol.clone(),
+            uri.clone(),
+        )?;
+
+        Ok(MintCreated {
+            mint: self.mint.key(),
+            authority: self.vault.key(),
+            decimals,
+            max_supply,
             name,
             symbol,
             uri,
+            default_frozen: extensions.default_frozen,
+            pausable: extensions.pausable,
+            transfer_fee_bps: extensions.transfer_fee.map(|fee| fee.basis_points),
+            ui_amount: extensions.ui_amount,
+        })
+    }
+
+    /// Before `initialize_vault` anyone may create the mint the vault is then initialized with.
+    fn check_admin(&self) -> Result<()> {
+        if self.vault.owner != &crate::ID {
+            return Ok(());
+        }
+
+        let vault = Vault::try_deserialize(&mut &self.vault.try_borrow_data()?[..])?;
+        require_keys_eq!(vault.admin, self.admin.key(), ErrorCode::Unauthorized);
+
+        Ok(())
+    }
+
+    /// Extensions must be initialized before `initialize_mint2`, with every authority on the vault.
+    fn initialize_extensions(&self, extensions: &MintExtensions) -> Result<()> {
+        let vault = self.vault.key();
+
+        transfer_hook_initialize(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferHookInitialize {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                },
+            ),
+            Some(vault),
+            Some(crate::ID),
+        )?;
+
+        metadata_pointer_initialize(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                MetadataPointerInitialize {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                },
+            ),
+            Some(vault),
+            Some(self.mint.key()),
         )?;
 
-        let cpi = CpiContext::new(
+        permanent_delegate_initialize(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                PermanentDelegateInitialize {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                },
+            ),
+            &vault,
+        )?;
+
+        if extensions.default_frozen {
+            let ix = default_account_state::instruction::initialize_default_account_state(
+                self.token_program.key,
+                &self.mint.key(),
+                &AccountState::Frozen,
+            )?;
+
+            invoke(&ix, &[self.mint.to_account_info()])?;
+        }
+
+        if extensions.pausable {
+            let ix = pausable::instruction::initialize(
+                self.token_program.key,
+                &self.mint.key(),
+                &vault,
+            )?;
+
+            invoke(&ix, &[self.mint.to_account_info()])?;
+        }
+
+        if let Some(transfer_fee) = extensions.transfer_fee {
+            transfer_fee_initialize(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    TransferFeeInitialize {
+                        token_program_id: self.token_program.to_account_info(),
+                        mint: self.mint.to_account_info(),
+                    },
+                ),
+                Some(&vault),
+                Some(&vault),
+                transfer_fee.basis_points,
+                transfer_fee.maximum_fee,
+            )?;
+        }
+
+        match extensions.ui_amount {
+            Some(UiAmountConfig::InterestBearing { rate }) => {
+                interest_bearing_mint_initialize(
+                    CpiContext::new(
+                        self.token_program.to_account_info(),
+                        InterestBearingMintInitialize {
+                            token_program_id: self.token_program.to_account_info(),
+                            mint: self.mint.to_account_info(),
+                        },
+                    ),
+                    Some(vault),
+                    rate,
+                )?;
+            }
+            Some(UiAmountConfig::ScaledUiAmount { multiplier }) => {
+                require!(
+                    multiplier.is_finite() && multiplier > 0.0,
+                    ErrorCode::InvalidMultiplier
+                );
+
+                let ix = scaled_ui_amount::instruction::initialize(
+                    self.token_program.key,
+                    &self.mint.key(),
+                    Some(vault),
+                    multiplier,
+                )?;
+
+                invoke(&ix, &[self.mint.to_account_info()])?;
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MintToWhitelisted<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub recipient: SystemAccount<'info>,
+
+    /// Only wallets with a `User` PDA may receive new supply
+    #[account(
+        seeds = [b"user", recipient.key().as_ref()],
+        bump = recipient_state.bump,
+        constraint = !recipient_state.suspended @ ErrorCode::NotWhitelisted,
+    )]
+    pub recipient_state: Account<'info, User>,
+
+    #[account(
+        mut,
+        mint::authority = vault,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"mint-config", mint.key().as_ref()],
+        bump = mint_config.bump,
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// CHECK: Mint policy PDA, may not exist, then the default rules apply
+    #[account(
+        seeds = [b"mint-policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> MintToWhitelisted<'info> {
+    pub fn mint_to_whitelisted(&mut self, amount: u64) -> Result<TokensMinted> {
+        let issued = self
+            .mint_config
+            .issued
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require_gte!(
+            self.mint_config.max_supply,
+            issued,
+            ErrorCode::SupplyCapExceeded
+        );
+        self.mint_config.issued = issued;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        let cpi = CpiContext::new_with_signer(
             self.token_program.to_account_info(),
             MintTo {
                 mint: self.mint.to_account_info(),
-                to: self.user_ata.to_account_info(),
-                authority: self.admin.to_account_info(),
+                to: self.recipient_ata.to_account_info(),
+                authority: self.vault.to_account_info(),
             },
+            signer_seeds,
         );
 
         token_interface::mint_to(cpi, amount)?;
 
-        Ok(())
+        // New supply does not pass through the hook, so its holding limit is checked here
+        self.recipient_ata.reload()?;
+        MintPolicy::load_rules(&self.mint_policy)?.check_holding(
+            &self.recipient_state,
+            true,
+            self.recipient_ata.amount,
+        )?;
+
+        Ok(TokensMinted {
+            mint: self.mint.key(),
+            recipient: self.recipient.key(),
+            amount,
+            issued,
+            max_supply: self.mint_config.max_supply,
+        })
     }
 }
@@ -1,15 +1,39 @@
+pub mod clawback;
+pub mod collect_fees;
 pub mod deposit;
+pub mod distribute_yield;
 pub mod init_extra_account_meta;
 pub mod init_vault;
+pub mod introspection;
+pub mod mint_policy;
 pub mod mint_token;
+pub mod pause;
+pub mod reconcile;
+pub mod sweep_surplus;
+pub mod token_metadata;
+pub mod transfer_fee;
 pub mod transfer_hook;
+pub mod ui_amount;
+pub mod vault_config;
 pub mod whitelist_operations;
 pub mod withdraw;
 
+pub use clawback::*;
+pub use collect_fees::*;
 pub use deposit::*;
+pub use distribute_yield::*;
 pub use init_extra_account_meta::*;
 pub use init_vault::*;
+pub use introspection::*;
+pub use mint_policy::*;
 pub use mint_token::*;
+pub use pause::*;
+pub use reconcile::*;
+pub use sweep_surplus::*;
+pub use token_metadata::*;
+pub use transfer_fee::*;
 pub use transfer_hook::*;
+pub use ui_amount::*;
+pub use vault_config::*;
 pub use whitelist_operations::*;
 pub use withdraw::*;
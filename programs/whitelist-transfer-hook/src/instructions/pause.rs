@@ -0,0 +1,77 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke_signed},
+};
+use anchor_spl::token_interface::{Mint, TokenInterface};
+use spl_token_2022::extension::pausable;
+
+use crate::{
+    events::{MintPaused, MintResumed},
+    state::Vault,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetMintPause<'info> {
+    pub admin: Signer<'info>,
+
+    /// Pause authority of pausable mints created by `create_mint`
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        mint::authority = vault,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> SetMintPause<'info> {
+    /// Halts every transfer, mint and burn of the mint at the token program level.
+    pub fn pause_mint(&mut self) -> Result<MintPaused> {
+        let ix = pausable::instruction::pause(
+            self.token_program.key,
+            &self.mint.key(),
+            &self.vault.key(),
+            &[],
+        )?;
+        self.invoke_signed_by_vault(&ix)?;
+
+        Ok(MintPaused {
+            mint: self.mint.key(),
+        })
+    }
+
+    pub fn resume_mint(&mut self) -> Result<MintResumed> {
+        let ix = pausable::instruction::resume(
+            self.token_program.key,
+            &self.mint.key(),
+            &self.vault.key(),
+            &[],
+        )?;
+        self.invoke_signed_by_vault(&ix)?;
+
+        Ok(MintResumed {
+            mint: self.mint.key(),
+        })
+    }
+
+    fn invoke_signed_by_vault(&self, ix: &Instruction) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        invoke_signed(
+            ix,
+            &[self.mint.to_account_info(), self.vault.to_account_info()],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+}
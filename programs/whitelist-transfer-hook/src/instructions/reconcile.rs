@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{error::ErrorCode, events::VaultReconciled, state::Vault};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+impl<'info> Reconcile<'info> {
+    pub fn reconcile(&self) -> Result<VaultReconciled> {
+        let vault_amount = self.vault_token_account.amount;
+        let liabilities = self.vault.liabilities().ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(VaultReconciled {
+            vault_amount,
+            liabilities,
+            surplus: vault_amount.saturating_sub(liabilities),
+            deficit: liabilities.saturating_sub(vault_amount),
+        })
+    }
+}
@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{approve, Approve, TokenAccount, TokenInterface};
+
+use crate::{
+    error::ErrorCode,
+    events::SurplusSwept,
+    instructions::check_transfer_instruction,
+    state::{User, Vault},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SweepSurplus<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+        has_one = vault_token_account,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Whitelist entry of the beneficiary wallet, the surplus goes to the treasury when omitted
+    #[account(mut)]
+    pub beneficiary_state: Option<Account<'info, User>>,
+
+    /// CHECK: instructions sysvar account
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> SweepSurplus<'info> {
+    /// Tokens held by the vault token account beyond everything the ledger owes, e.g. from a bare
+    /// `transfer_checked` into the vault that never went through `deposit`.
+    pub fn surplus(&self) -> Result<u64> {
+        let liabilities = self.vault.liabilities().ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(self.vault_token_account.amount.saturating_sub(liabilities))
+    }
+
+    /// Credits the surplus as shares to the `beneficiary` wallet, or approves the admin to move
+    /// it to the treasury in the next instruction.
+    pub fn sweep_surplus(&mut self, beneficiary: Option<Pubkey>) -> Result<SurplusSwept> {
+        let amount = self.surplus()?;
+        require_gt!(amount, 0, ErrorCode::NoSurplus);
+
+        match (beneficiary, self.beneficiary_state.as_mut()) {
+            (Some(wallet), Some(beneficiary_state)) => {
+                let expected = Pubkey::create_program_address(
+                    &[b"user", wallet.as_ref(), &[beneficiary_state.bump]],
+                    &crate::ID,
+                )
+                .map_err(|_| ErrorCode::InvalidBeneficiary)?;
+                require_keys_eq!(
+                    beneficiary_state.key(),
+                    expected,
+                    ErrorCode::InvalidBeneficiary
+                );
+
+                let shares = self
+                    .vault
+                    .shares_for_deposit(amount)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                require_gt!(shares, 0, ErrorCode::ZeroShares);
+
+                beneficiary_state.shares = beneficiary_state
+                    .shares
+                    .checked_add(shares)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                self.vault.total_shares = self
+                    .vault
+                    .total_shares
+                    .checked_add(shares)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                self.vault.total_outstanding = self
+                    .vault
+                    .total_outstanding
+                    .checked_add(amount)
+                    .ok_or(ErrorCode::MathOverflow)?;
+
+                Ok(SurplusSwept {
+                    amount,
+                    beneficiary: Some(wallet),
+                    shares,
+                    treasury: None,
+                })
+            }
+            (None, None) => {
+                let transferred = check_transfer_instruction(
+                    &self.instructions,
+                    1,
+                    Some(self.vault_token_account.key()),
+                    Some(self.vault.treasury),
+                    self.admin.key(),
+                )?;
+                require_eq!(transferred, amount);
+
+                let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+                approve(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        Approve {
+                            to: self.vault_token_account.to_account_info(),
+                            delegate: self.admin.to_account_info(),
+                            authority: self.vault.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    amount,
+                )?;
+
+                Ok(SurplusSwept {
+                    amount,
+                    beneficiary: None,
+                    shares: 0,
+                    treasury: Some(self.vault.treasury),
+                })
+            }
+            _ => err!(ErrorCode::InvalidBeneficiary),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::program::invoke_signed,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::token_interface::{
+    spl_pod::optional_keys::OptionalNonZeroPubkey, Mint, TokenInterface,
+};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, StateWithExtensions},
+    state::Mint as MintState,
+};
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+
+use crate::{
+    events::{MetadataAuthorityUpdated, MetadataFieldUpdated, MetadataKeyRemoved},
+    state::Vault,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum MetadataField {
+    Name,
+    Symbol,
+    Uri,
+    /// Custom key, e.g. `isin`, `jurisdiction` or `prospectus_hash`
+    Key(String),
+}
+
+impl From<MetadataField> for Field {
+    fn from(field: MetadataField) -> Self {
+        match field {
+            MetadataField::Name => Field::Name,
+            MetadataField::Symbol => Field::Symbol,
+            MetadataField::Uri => Field::Uri,
+            MetadataField::Key(key) => Field::Key(key),
+        }
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateTokenMetadata<'info> {
+    /// Pays for rent top-ups and receives refunds
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Metadata update authority of mints created by `create_mint`
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> UpdateTokenMetadata<'info> {
+    pub fn update_metadata_field(
+        &mut self,
+        field: MetadataField,
+        value: String,
+    ) -> Result<MetadataFieldUpdated> {
+        let mut metadata = self.token_metadata()?;
+        metadata.update(field.clone().into(), value.clone());
+        self.top_up_rent(&metadata)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        let ix = spl_token_metadata_interface::instruction::update_field(
+            self.token_program.key,
+            &self.mint.key(),
+            &self.vault.key(),
+            field.clone().into(),
+            value.clone(),
+        );
+
+        invoke_signed(
+            &ix,
+            &[self.mint.to_account_info(), self.vault.to_account_info()],
+            signer_seeds,
+        )?;
+
+        self.refund_excess_rent()?;
+
+        Ok(MetadataFieldUpdated {
+            mint: self.mint.key(),
+            field,
+            value,
+        })
+    }
+
+    pub fn remove_metadata_key(&mut self, key: String) -> Result<MetadataKeyRemoved> {
+        let ix = spl_token_metadata_interface::instruction::remove_key(
+            self.token_program.key,
+            &self.mint.key(),
+            &self.vault.key(),
+            key.clone(),
+            false,
+        );
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        invoke_signed(
+            &ix,
+            &[self.mint.to_account_info(), self.vault.to_account_info()],
+            signer_seeds,
+        )?;
+
+        self.refund_excess_rent()?;
+
+        Ok(MetadataKeyRemoved {
+            mint: self.mint.key(),
+            key,
+        })
+    }
+
+    /// Hands the update authority to `new_authority`, or renounces it for good when `None`.
+    pub fn update_metadata_authority(
+        &mut self,
+        new_authority: Option<Pubkey>,
+    ) -> Result<MetadataAuthorityUpdated> {
+        let new_authority_key = OptionalNonZeroPubkey::try_from(new_authority)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        let ix = spl_token_metadata_interface::instruction::update_authority(
+            self.token_program.key,
+            &self.mint.key(),
+            &self.vault.key(),
+            new_authority_key,
+        );
+
+        invoke_signed(
+            &ix,
+            &[self.mint.to_account_info(), self.vault.to_account_info()],
+            signer_seeds,
+        )?;
+
+        Ok(MetadataAuthorityUpdated {
+            mint: self.mint.key(),
+            new_authority,
+        })
+    }
+
+    fn token_metadata(&self) -> Result<TokenMetadata> {
+        let mint_info = self.mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<MintState>::unpack(&mint_data)?;
+
+        Ok(mint.get_variable_len_extension::<TokenMetadata>()?)
+    }
+
+    /// Funds the mint so it stays rent exempt once the metadata grows to `metadata`.
+    fn top_up_rent(&self, metadata: &TokenMetadata) -> Result<()> {
+        let mint_info = self.mint.to_account_info();
+        let current_len = packed_len(&self.token_metadata()?)?;
+        let new_len = (mint_info.data_len() + packed_len(metadata)?).saturating_sub(current_len);
+
+        let required = Rent::get()?.minimum_balance(new_len);
+        let lamports = required.saturating_sub(mint_info.lamports());
+        if lamports == 0 {
+            return Ok(());
+        }
+
+        transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.admin.to_account_info(),
+                    to: mint_info,
+                },
+            ),
+            lamports,
+        )
+    }
+
+    /// Returns rent freed by a shrinking metadata to the admin. Token-2022 only lets the mint
+    /// authority withdraw a mint's excess lamports, so nothing is refunded otherwise.
+    fn refund_excess_rent(&self) -> Result<()> {
+        if self.mint.mint_authority != Some(self.vault.key()).into() {
+            return Ok(());
+        }
+
+        let mint_info = self.mint.to_account_info();
+        let required = Rent::get()?.minimum_balance(mint_info.data_len());
+        if mint_info.lamports() <= required {
+            return Ok(());
+        }
+
+        let ix = spl_token_2022::instruction::withdraw_excess_lamports(
+            self.token_program.key,
+            &self.mint.key(),
+            self.admin.key,
+            &self.vault.key(),
+            &[],
+        )?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        invoke_signed(
+            &ix,
+            &[
+                mint_info,
+                self.admin.to_account_info(),
+                self.vault.to_account_info(),
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+}
+
+fn packed_len(metadata: &TokenMetadata) -> Result<usize> {
+    spl_token_metadata_interface::solana_borsh::v1::get_instance_packed_len(metadata)
+        .map_err(|_| ProgramError::InvalidAccountData.into())
+}
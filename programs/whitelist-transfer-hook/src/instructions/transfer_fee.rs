@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    harvest_withheld_tokens_to_mint, withdraw_withheld_tokens_from_mint,
+    HarvestWithheldTokensToMint, Mint, TokenAccount, TokenInterface,
+    WithdrawWithheldTokensFromMint,
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+    state::Mint as MintState,
+};
+
+use crate::{
+    error::ErrorCode,
+    events::{TransferFeesHarvested, TransferFeesWithdrawn},
+    state::Vault,
+};
+
+/// Fee Token-2022 withholds from a transfer of `amount` this epoch, zero for mints without the
+/// transfer-fee extension.
+pub fn transfer_fee_for(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+
+    // A fully packed TLV buffer reports a missing extension as `InvalidAccountData` rather than
+    // `ExtensionNotFound`, so absence is checked up front and any other failure propagates
+    if !mint
+        .get_extension_types()?
+        .contains(&ExtensionType::TransferFeeConfig)
+    {
+        return Ok(0);
+    }
+
+    let config = mint.get_extension::<TransferFeeConfig>()?;
+    Ok(config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(ErrorCode::MathOverflow)?)
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct HarvestTransferFees<'info> {
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> HarvestTransferFees<'info> {
+    /// Permissionless crank, moves the fees withheld in every token account passed as a remaining
+    /// account into the mint, where `withdraw_transfer_fees` can collect them.
+    pub fn harvest_transfer_fees(
+        &mut self,
+        sources: &'info [AccountInfo<'info>],
+    ) -> Result<TransferFeesHarvested> {
+        harvest_withheld_tokens_to_mint(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                HarvestWithheldTokensToMint {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                },
+            ),
+            sources.to_vec(),
+        )?;
+
+        Ok(TransferFeesHarvested {
+            mint: self.mint.key(),
+            accounts: sources.len() as u32,
+        })
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawTransferFees<'info> {
+    pub admin: Signer<'info>,
+
+    /// Withdraw-withheld authority of mints created by `create_mint`
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+        has_one = treasury,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        mint::authority = vault,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Holds the vault mint, its owner receives the fees of every mint
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+
+    /// Account of the treasury's owner for `mint`, the treasury itself for the vault mint
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = treasury.owner,
+        token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> WithdrawTransferFees<'info> {
+    pub fn withdraw_transfer_fees(&mut self) -> Result<TransferFeesWithdrawn> {
+        let amount = self.withheld_amount()?;
+        require_gt!(amount, 0, ErrorCode::NoFees);
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        withdraw_withheld_tokens_from_mint(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            WithdrawWithheldTokensFromMint {
+                token_program_id: self.token_program.to_account_info(),
+                mint: self.mint.to_account_info(),
+                destination: self.destination.to_account_info(),
+                authority: self.vault.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        Ok(TransferFeesWithdrawn {
+            mint: self.mint.key(),
+            treasury: self.destination.key(),
+            amount,
+        })
+    }
+
+    fn withheld_amount(&self) -> Result<u64> {
+        let mint_info = self.mint.to_account_info();
+        let data = mint_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+        let config = mint.get_extension::<TransferFeeConfig>()?;
+
+        Ok(config.withheld_amount.into())
+    }
+}
@@ -2,24 +2,61 @@ use std::cell::RefMut;
 
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token_2022::spl_token_2022::{
-        extension::{
-            transfer_hook::TransferHookAccount, BaseStateWithExtensionsMut,
-            PodStateWithExtensionsMut,
-        },
-        pod::PodAccount,
-    },
+    associated_token::get_associated_token_address_with_program_id,
     token_interface::{Mint, TokenAccount},
 };
+use spl_token_2022::{
+    extension::{
+        transfer_hook::TransferHookAccount, BaseStateWithExtensionsMut, PodStateWithExtensionsMut,
+    },
+    pod::PodAccount,
+};
+
+use crate::{
+    error::ErrorCode,
+    events::{PolicyViolation, TransferAllowed, TransferRejected},
+    state::{EnforcementMode, MintPolicy, PolicyRules, TransferLog, TransferRecord, User},
+};
+
+/// First rule of the mint policy a transfer breaks
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViolationReason {
+    AuthorityNotWhitelisted,
+    SourceOwnerNotWhitelisted,
+    DestinationOwnerNotWhitelisted,
+    OutsideTradingWindow,
+    JurisdictionNotAllowed,
+    MaxHoldingExceeded,
+    HoldingOutsideAssociatedAccount,
+}
+
+impl ViolationReason {
+    pub fn error(&self) -> ErrorCode {
+        match self {
+            Self::AuthorityNotWhitelisted
+            | Self::SourceOwnerNotWhitelisted
+            | Self::DestinationOwnerNotWhitelisted => ErrorCode::NotWhitelisted,
+            Self::OutsideTradingWindow => ErrorCode::TradingWindowClosed,
+            Self::JurisdictionNotAllowed => ErrorCode::JurisdictionNotAllowed,
+            Self::MaxHoldingExceeded => ErrorCode::MaxHoldingExceeded,
+            Self::HoldingOutsideAssociatedAccount => ErrorCode::HoldingOutsideAssociatedAccount,
+        }
+    }
+}
 
-use crate::state::{User, Vault};
+pub enum TransferOutcome {
+    Allowed(TransferAllowed),
+    /// Allowed in monitor mode despite breaking the policy
+    Flagged(TransferAllowed, PolicyViolation),
+    Rejected(TransferRejected),
+}
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct TransferHook<'info> {
+    /// Not constrained to `authority`, outflows from the vault are signed by a delegate
     #[account(
         token::mint = mint,
-        // we are using delegation, so this will fail withdraw
-        // token::authority = owner,
     )]
     pub source_token: InterfaceAccount<'info, TokenAccount>,
 
@@ -30,8 +67,8 @@ pub struct TransferHook<'info> {
     )]
     pub destination_token: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: source token account owner, can be SystemAccount or PDA owned by another program
-    pub owner: UncheckedAccount<'info>,
+    /// CHECK: transfer authority, the source token account owner or a delegate signing for it
+    pub authority: UncheckedAccount<'info>,
 
     /// CHECK: ExtraAccountMetaList Account,
     #[account(
@@ -40,36 +77,172 @@ pub struct TransferHook<'info> {
     )]
     pub extra_account_meta_list: UncheckedAccount<'info>,
 
+    /// CHECK: whitelist entry of the transfer authority, may not exist
+    #[account(
+        seeds = [b"user", authority.key().as_ref()],
+        bump,
+    )]
+    pub authority_user: UncheckedAccount<'info>,
+
+    /// CHECK: whitelist entry of the source token account owner, may not exist
     #[account(
-        seeds = [b"vault"],
-        bump = vault.bump,
+        seeds = [b"user", source_token.owner.as_ref()],
+        bump,
     )]
-    pub vault: Account<'info, Vault>,
+    pub owner_user: UncheckedAccount<'info>,
 
+    /// CHECK: whitelist entry of the destination token account owner, may not exist
     #[account(
-        seeds = [b"user", owner.key().as_ref()],
-        bump = user.bump,
+        seeds = [b"user", destination_token.owner.as_ref()],
+        bump,
     )]
-    pub user: Account<'info, User>,
+    pub destination_user: UncheckedAccount<'info>,
+
+    /// CHECK: Mint policy PDA, may not exist, then the default rules apply
+    #[account(
+        seeds = [b"mint-policy", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_policy: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"transfer-log", mint.key().as_ref()],
+        bump = transfer_log.load()?.bump,
+    )]
+    pub transfer_log: AccountLoader<'info, TransferLog>,
 }
 
 impl<'info> TransferHook<'info> {
     /// This function is called when the transfer hook is executed.
-    pub fn transfer_hook(&mut self, _amount: u64) -> Result<()> {
+    pub fn transfer_hook(&mut self, amount: u64) -> Result<TransferOutcome> {
         // Fail this instruction if it is not called from within a transfer hook
 
         self.check_is_transferring()?;
 
-        msg!("Source token owner: {}", self.source_token.owner);
-        msg!("Destination token owner: {}", self.destination_token.owner);
+        let rules = MintPolicy::load_rules(&self.mint_policy)?;
+        let now = Clock::get()?.unix_timestamp;
+        let violation = match rules.mode {
+            EnforcementMode::Off => None,
+            EnforcementMode::Monitor | EnforcementMode::Enforce => self.check_policy(&rules, now),
+        };
+
+        if let Some(reason) = violation {
+            if rules.mode == EnforcementMode::Enforce {
+                return Ok(TransferOutcome::Rejected(TransferRejected {
+                    mint: self.mint.key(),
+                    source_owner: self.source_token.owner,
+                    destination_owner: self.destination_token.owner,
+                    authority: self.authority.key(),
+                    amount,
+                    reason,
+                }));
+            }
+        }
 
-        // if self.whitelist.address.contains(&self.source_token.owner) {
-        //     msg!("Transfer allowed: The address is whitelisted");
-        // } else {
-        //     panic!("TransferHook: Address is not whitelisted");
-        // }
+        self.transfer_log.load_mut()?.append(TransferRecord {
+            slot: Clock::get()?.slot,
+            source_owner: self.source_token.owner,
+            destination_owner: self.destination_token.owner,
+            amount,
+        });
 
-        Ok(())
+        let allowed = TransferAllowed {
+            mint: self.mint.key(),
+            source_owner: self.source_token.owner,
+            destination_owner: self.destination_token.owner,
+            authority: self.authority.key(),
+            amount,
+        };
+
+        Ok(match violation {
+            Some(reason) => TransferOutcome::Flagged(
+                allowed,
+                PolicyViolation {
+                    mint: self.mint.key(),
+                    source_owner: self.source_token.owner,
+                    destination_owner: self.destination_token.owner,
+                    authority: self.authority.key(),
+                    amount,
+                    reason,
+                },
+            ),
+            None => TransferOutcome::Allowed(allowed),
+        })
+    }
+
+    /// Whoever signs the transfer must always be whitelisted, the owners on either side as the
+    /// mint policy demands. The exempt owner, normally the vault, has no whitelist entry, its
+    /// tokens only leave through a delegate approved by the program.
+    fn check_policy(&self, rules: &PolicyRules, now: i64) -> Option<ViolationReason> {
+        if Self::load_user(&self.authority_user).is_none() {
+            return Some(ViolationReason::AuthorityNotWhitelisted);
+        }
+
+        let source_owner = self.source_token.owner;
+        let source_exempt = source_owner == rules.exempt_owner;
+        let source_user = Self::load_user(&self.owner_user);
+        if rules.check_source_owner && !source_exempt && source_user.is_none() {
+            return Some(ViolationReason::SourceOwnerNotWhitelisted);
+        }
+
+        let destination_owner = self.destination_token.owner;
+        let destination_exempt = destination_owner == rules.exempt_owner;
+        let destination_user = Self::load_user(&self.destination_user);
+        if rules.check_destination_owner && !destination_exempt && destination_user.is_none() {
+            return Some(ViolationReason::DestinationOwnerNotWhitelisted);
+        }
+
+        if !rules.trading_window.is_open(now) {
+            return Some(ViolationReason::OutsideTradingWindow);
+        }
+
+        if rules.check_jurisdictions && !source_exempt && !destination_exempt {
+            let allowed = match (&source_user, &destination_user) {
+                (Some(source), Some(destination)) => {
+                    rules.allows_jurisdictions(source.jurisdiction, destination.jurisdiction)
+                }
+                _ => false,
+            };
+            if !allowed {
+                return Some(ViolationReason::JurisdictionNotAllowed);
+            }
+        }
+
+        // The token program has already credited the destination when the hook runs. The limit
+        // is checked against one account only, so a limited holder may not spread its balance
+        // over other token accounts.
+        if !destination_exempt {
+            if let Some(max_holding) = rules.holding_limit(destination_user.as_ref()) {
+                let associated = get_associated_token_address_with_program_id(
+                    &destination_owner,
+                    &self.mint.key(),
+                    self.mint.to_account_info().owner,
+                );
+                if self.destination_token.key() != associated {
+                    return Some(ViolationReason::HoldingOutsideAssociatedAccount);
+                }
+                if self.destination_token.amount > max_holding {
+                    return Some(ViolationReason::MaxHoldingExceeded);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The wallet's whitelist entry, if its `User` PDA exists, is owned by this program and is
+    /// not suspended.
+    fn load_user(user: &UncheckedAccount) -> Option<User> {
+        if user.owner != &crate::ID {
+            return None;
+        }
+
+        let data = user.try_borrow_data().ok()?;
+
+        User::try_deserialize(&mut &data[..])
+            .ok()
+            .filter(|user| !user.suspended)
     }
 
     /// Checks if the transfer hook is being executed during a transfer operation.
@@ -0,0 +1,103 @@
+use anchor_lang::{prelude::*, solana_program::program::invoke_signed};
+use anchor_spl::token_interface::{
+    interest_bearing_mint_update_rate, InterestBearingMintUpdateRate, Mint, TokenInterface,
+};
+use spl_token_2022::extension::scaled_ui_amount;
+
+use crate::{
+    error::ErrorCode,
+    events::{InterestRateUpdated, UiMultiplierUpdated},
+    state::Vault,
+};
+
+/// How UI amounts of the mint are derived from raw amounts. Both only change what wallets
+/// display, raw amounts never move, so the vault ledger which works in raw amounts is unaffected.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum UiAmountConfig {
+    /// Continuously compounding rate in basis points
+    InterestBearing { rate: i16 },
+    /// Fixed multiplier, e.g. updated for stock splits
+    ScaledUiAmount { multiplier: f64 },
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateUiAmount<'info> {
+    pub admin: Signer<'info>,
+
+    /// Rate and multiplier authority of mints created by `create_mint`
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        mint::authority = vault,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> UpdateUiAmount<'info> {
+    pub fn update_interest_rate(&mut self, rate: i16) -> Result<InterestRateUpdated> {
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        interest_bearing_mint_update_rate(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                InterestBearingMintUpdateRate {
+                    token_program_id: self.token_program.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    rate_authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            rate,
+        )?;
+
+        Ok(InterestRateUpdated {
+            mint: self.mint.key(),
+            rate,
+        })
+    }
+
+    /// Schedules a new multiplier, which wallets apply from `effective_timestamp` on.
+    pub fn update_ui_multiplier(
+        &mut self,
+        multiplier: f64,
+        effective_timestamp: i64,
+    ) -> Result<UiMultiplierUpdated> {
+        require!(
+            multiplier.is_finite() && multiplier > 0.0,
+            ErrorCode::InvalidMultiplier
+        );
+
+        let ix = scaled_ui_amount::instruction::update_multiplier(
+            self.token_program.key,
+            &self.mint.key(),
+            &self.vault.key(),
+            &[],
+            multiplier,
+            effective_timestamp,
+        )?;
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        invoke_signed(
+            &ix,
+            &[self.mint.to_account_info(), self.vault.to_account_info()],
+            signer_seeds,
+        )?;
+
+        Ok(UiMultiplierUpdated {
+            mint: self.mint.key(),
+            multiplier,
+            effective_timestamp,
+        })
+    }
+}
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{thaw_account, Mint, ThawAccount, TokenAccount, TokenInterface};
+
+use crate::{
+    error::ErrorCode,
+    events::{FeesUpdated, TreasuryUpdated, WithdrawCooldownUpdated},
+    state::{Vault, MAX_FEE_BPS},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateVaultConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+impl<'info> UpdateVaultConfig<'info> {
+    pub fn set_withdraw_cooldown(
+        &mut self,
+        withdraw_cooldown: i64,
+    ) -> Result<WithdrawCooldownUpdated> {
+        require_gte!(withdraw_cooldown, 0, ErrorCode::InvalidCooldown);
+
+        self.vault.withdraw_cooldown = withdraw_cooldown;
+
+        Ok(WithdrawCooldownUpdated { withdraw_cooldown })
+    }
+
+    pub fn set_fees(&mut self, deposit_fee_bps: u16, withdraw_fee_bps: u16) -> Result<FeesUpdated> {
+        require_gte!(MAX_FEE_BPS, deposit_fee_bps, ErrorCode::InvalidFee);
+        require_gte!(MAX_FEE_BPS, withdraw_fee_bps, ErrorCode::InvalidFee);
+
+        self.vault.deposit_fee_bps = deposit_fee_bps;
+        self.vault.withdraw_fee_bps = withdraw_fee_bps;
+
+        Ok(FeesUpdated {
+            deposit_fee_bps,
+            withdraw_fee_bps,
+        })
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+        has_one = mint,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Fee and surplus payouts in the vault mint land in it, thawed here when that mint creates
+    /// accounts frozen. Secondary mints cannot, so their accounts never need thawing.
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> SetTreasury<'info> {
+    pub fn set_treasury(&mut self) -> Result<TreasuryUpdated> {
+        self.vault.treasury = self.treasury.key();
+
+        if self.treasury.is_frozen() {
+            let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+            thaw_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                ThawAccount {
+                    account: self.treasury.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        Ok(TreasuryUpdated {
+            treasury: self.treasury.key(),
+        })
+    }
+}
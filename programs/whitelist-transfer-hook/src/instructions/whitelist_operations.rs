@@ -1,15 +1,30 @@
 use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        freeze_account, thaw_account, FreezeAccount, Mint, ThawAccount, TokenAccount,
+        TokenInterface,
+    },
+};
 
-use crate::state::{User, Vault};
+use crate::{
+    error::ErrorCode,
+    events::{JurisdictionUpdated, MaxHoldingUpdated, UserRemoved, UserWhitelisted},
+    state::{validate_jurisdiction, User, Vault},
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(address: Pubkey)]
 pub struct AddToWhitelist<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
 
+    /// Whitelisting covers the vault mint, the only one whose accounts can start frozen
     #[account(
+        mut,
         has_one = admin,
+        has_one = mint,
     )]
     pub vault: Account<'info, Vault>,
 
@@ -22,24 +37,76 @@ pub struct AddToWhitelist<'info> {
     )]
     pub user: Account<'info, User>,
 
+    /// CHECK: wallet being whitelisted, only needed to derive its token account
+    #[account(address = address)]
+    pub wallet: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Thawed here when the mint creates accounts frozen
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = mint,
+        associated_token::authority = wallet,
+        associated_token::token_program = token_program
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
     pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> AddToWhitelist<'info> {
     pub fn add_to_whitelist(
         &mut self,
-        _address: Pubkey,
+        address: Pubkey,
+        jurisdiction: [u8; 2],
         bumps: &AddToWhitelistBumps,
-    ) -> Result<()> {
+    ) -> Result<UserWhitelisted> {
+        validate_jurisdiction(&jurisdiction)?;
+
         self.user.set_inner(User {
-            balance: 0,
+            shares: 0,
+            locks: Vec::new(),
             bump: bumps.user,
+            jurisdiction,
+            max_holding: None,
+            suspended: false,
         });
 
-        Ok(())
+        self.vault.user_count = self
+            .vault
+            .user_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        if self.user_token_account.is_frozen() {
+            let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+            thaw_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                ThawAccount {
+                    account: self.user_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        Ok(UserWhitelisted {
+            user: address,
+            jurisdiction,
+        })
     }
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(address: Pubkey)]
 pub struct RemoveFromWhitelist<'info> {
@@ -47,23 +114,262 @@ pub struct RemoveFromWhitelist<'info> {
     pub admin: Signer<'info>,
 
     #[account(
+        mut,
         has_one = admin,
+        has_one = mint,
     )]
     pub vault: Account<'info, Vault>,
 
     #[account(
         mut,
-        close = admin,
         seeds = [b"user", address.key().as_ref()],
         bump = user.bump,
     )]
     pub user: Account<'info, User>,
 
+    #[account(
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Re-frozen so tokens cannot leave it even if the hook is bypassed or misconfigured.
+    /// Omitted when the wallet never opened its associated token account.
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = address,
+        associated_token::token_program = token_program
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
     pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 impl<'info> RemoveFromWhitelist<'info> {
-    pub fn remove_from_whitelist(&mut self, _address: Pubkey) -> Result<()> {
-        Ok(())
+    /// Takes the wallet off the whitelist and freezes its associated token account. The hook
+    /// rejects the wallet on every transfer from then on, whatever account it uses; any other
+    /// token accounts it owns for the mint can be passed as remaining accounts to be frozen as
+    /// well.
+    ///
+    /// An entry without shares or locks is closed. Otherwise it is kept but suspended, so the
+    /// wallet cannot hold off its removal by keeping a deposit: the shares stay on the books
+    /// and can be withdrawn once `reinstate_user` lifts the suspension. Removing a suspended
+    /// entry again freezes further accounts, or closes it once its shares are gone.
+    pub fn remove_from_whitelist(
+        &mut self,
+        address: Pubkey,
+        other_token_accounts: &'info [AccountInfo<'info>],
+    ) -> Result<UserRemoved> {
+        if !self.user.suspended {
+            self.vault.user_count = self
+                .vault
+                .user_count
+                .checked_sub(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        let suspended = self.user.shares > 0 || !self.user.locks.is_empty();
+        if suspended {
+            self.user.suspended = true;
+        } else {
+            self.user.close(self.admin.to_account_info())?;
+        }
+
+        // Mints created before the vault held the freeze authority cannot be frozen
+        if self.mint.freeze_authority != Some(self.vault.key()).into() {
+            return Ok(UserRemoved {
+                user: address,
+                suspended,
+            });
+        }
+
+        if let Some(user_token_account) = &self.user_token_account {
+            self.freeze(user_token_account)?;
+        }
+
+        for info in other_token_accounts {
+            let token_account = InterfaceAccount::<TokenAccount>::try_from(info)?;
+            require!(
+                token_account.mint == self.mint.key() && token_account.owner == address,
+                ErrorCode::ForeignTokenAccount
+            );
+            self.freeze(&token_account)?;
+        }
+
+        Ok(UserRemoved {
+            user: address,
+            suspended,
+        })
+    }
+
+    fn freeze(&self, token_account: &InterfaceAccount<'info, TokenAccount>) -> Result<()> {
+        if token_account.is_frozen() {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+        freeze_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            FreezeAccount {
+                account: token_account.to_account_info(),
+                mint: self.mint.to_account_info(),
+                authority: self.vault.to_account_info(),
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(address: Pubkey)]
+pub struct ReinstateUser<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user", address.key().as_ref()],
+        bump = user.bump,
+        constraint = user.suspended @ ErrorCode::UserNotSuspended,
+    )]
+    pub user: Account<'info, User>,
+
+    #[account(
+        mint::authority = vault,
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Thawed again if the removal froze it
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = address,
+        associated_token::token_program = token_program
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ReinstateUser<'info> {
+    /// Lifts the suspension of a removed wallet that still held shares, with its jurisdiction
+    /// and holding limit as they were.
+    pub fn reinstate_user(&mut self, address: Pubkey) -> Result<UserWhitelisted> {
+        self.user.suspended = false;
+
+        self.vault.user_count = self
+            .vault
+            .user_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        if let Some(user_token_account) = &self.user_token_account {
+            if user_token_account.is_frozen()
+                && self.mint.freeze_authority == Some(self.vault.key()).into()
+            {
+                let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
+
+                thaw_account(CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    ThawAccount {
+                        account: user_token_account.to_account_info(),
+                        mint: self.mint.to_account_info(),
+                        authority: self.vault.to_account_info(),
+                    },
+                    signer_seeds,
+                ))?;
+            }
+        }
+
+        Ok(UserWhitelisted {
+            user: address,
+            jurisdiction: self.user.jurisdiction,
+        })
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(address: Pubkey)]
+pub struct SetJurisdiction<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user", address.key().as_ref()],
+        bump = user.bump,
+    )]
+    pub user: Account<'info, User>,
+}
+
+impl<'info> SetJurisdiction<'info> {
+    pub fn set_jurisdiction(
+        &mut self,
+        address: Pubkey,
+        jurisdiction: [u8; 2],
+    ) -> Result<JurisdictionUpdated> {
+        validate_jurisdiction(&jurisdiction)?;
+
+        self.user.jurisdiction = jurisdiction;
+
+        Ok(JurisdictionUpdated {
+            user: address,
+            jurisdiction,
+        })
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(address: Pubkey)]
+pub struct SetMaxHolding<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"vault"],
+        bump = vault.bump,
+        has_one = admin,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user", address.key().as_ref()],
+        bump = user.bump,
+    )]
+    pub user: Account<'info, User>,
+}
+
+impl<'info> SetMaxHolding<'info> {
+    /// `None` falls back to the mint policy's limit.
+    pub fn set_max_holding(
+        &mut self,
+        address: Pubkey,
+        max_holding: Option<u64>,
+    ) -> Result<MaxHoldingUpdated> {
+        self.user.max_holding = max_holding;
+
+        Ok(MaxHoldingUpdated {
+            user: address,
+            max_holding,
+        })
     }
 }
@@ -1,15 +1,18 @@
-use anchor_lang::{
-    prelude::*,
-    solana_program::sysvar::instructions::{
-        load_current_index_checked, load_instruction_at_checked,
-    },
-};
+use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{approve, Approve, TokenAccount, TokenInterface};
 
-use crate::state::{User, Vault};
+use crate::{
+    error::ErrorCode,
+    events::{WithdrawProcessed, WithdrawRequested, Withdrawn},
+    instructions::check_transfer_instruction,
+    state::{User, Vault, WithdrawRequest},
+};
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct Withdraw<'info> {
+#[instruction(amount: u64, index: u64)]
+pub struct RequestWithdraw<'info> {
+    #[account(mut)]
     pub user: Signer<'info>,
 
     #[account(
@@ -25,6 +28,146 @@ pub struct Withdraw<'info> {
     )]
     pub user_account: Account<'info, User>,
 
+    /// Indexed so a user can have several requests pending at once
+    #[account(
+        init,
+        payer = user,
+        space = WithdrawRequest::DISCRIMINATOR.len() + WithdrawRequest::INIT_SPACE,
+        seeds = [b"withdraw-request", user.key().as_ref(), &index.to_le_bytes()],
+        bump,
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RequestWithdraw<'info> {
+    /// Escrows the shares backing `amount` until the vault's cooldown has passed.
+    pub fn request_withdraw(
+        &mut self,
+        amount: u64,
+        index: u64,
+        bumps: &RequestWithdrawBumps,
+    ) -> Result<WithdrawRequested> {
+        require_gt!(amount, 0, ErrorCode::ZeroAmount);
+
+        let shares = self
+            .vault
+            .shares_for_withdraw(amount)
+            .ok_or(ErrorCode::InsufficientBalance)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        self.user_account.prune_locks(now);
+        require_gte!(
+            self.user_account.shares,
+            shares,
+            ErrorCode::InsufficientBalance
+        );
+        require_gte!(
+            self.user_account.unlocked_shares(now),
+            shares,
+            ErrorCode::SharesLocked
+        );
+
+        self.user_account.shares = self
+            .user_account
+            .shares
+            .checked_sub(shares)
+            .ok_or(ErrorCode::InsufficientBalance)?;
+
+        let unlock_ts = now
+            .checked_add(self.vault.withdraw_cooldown)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        self.withdraw_request.set_inner(WithdrawRequest {
+            user: self.user.key(),
+            index,
+            amount,
+            shares,
+            unlock_ts,
+            payout: 0,
+            fee: 0,
+            processed: false,
+            bump: bumps.withdraw_request,
+        });
+
+        Ok(WithdrawRequested {
+            user: self.user.key(),
+            index,
+            amount,
+            shares,
+            unlock_ts,
+        })
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProcessWithdrawals<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+impl<'info> ProcessWithdrawals<'info> {
+    /// Permissionless crank, fixes the payout of every matured request passed as a remaining
+    /// account. Requests that are immature or already processed are skipped.
+    pub fn process_withdrawals(
+        &mut self,
+        requests: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<WithdrawProcessed>> {
+        let now = Clock::get()?.unix_timestamp;
+        let mut events = Vec::with_capacity(requests.len());
+
+        for info in requests {
+            let mut request = Account::<WithdrawRequest>::try_from(info)?;
+
+            if request.processed || !request.is_matured(now) {
+                continue;
+            }
+
+            let payout = request.process(&mut self.vault)?;
+            request.exit(&crate::ID)?;
+
+            events.push(WithdrawProcessed {
+                user: request.user,
+                index: request.index,
+                shares: request.shares,
+                payout,
+                fee: request.fee,
+            });
+        }
+
+        Ok(events)
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct ClaimWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"withdraw-request", user.key().as_ref(), &index.to_le_bytes()],
+        bump = withdraw_request.bump,
+        has_one = user,
+    )]
+    pub withdraw_request: Account<'info, WithdrawRequest>,
+
     #[account(
         mut,
         token::mint = vault.mint,
@@ -40,9 +183,39 @@ pub struct Withdraw<'info> {
     pub token_program: Interface<'info, TokenInterface>,
 }
 
-impl<'info> Withdraw<'info> {
-    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
-        require_gte!(self.user_account.balance, amount);
+impl<'info> ClaimWithdraw<'info> {
+    /// Processes the request if the crank has not done so yet, then approves the user to move
+    /// the payout out of the vault in the next instruction.
+    pub fn claim_withdraw(&mut self, index: u64) -> Result<(Option<WithdrawProcessed>, Withdrawn)> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            self.withdraw_request.is_matured(now),
+            ErrorCode::WithdrawNotMatured
+        );
+
+        let processed = if self.withdraw_request.processed {
+            None
+        } else {
+            let payout = self.withdraw_request.process(&mut self.vault)?;
+
+            Some(WithdrawProcessed {
+                user: self.user.key(),
+                index,
+                shares: self.withdraw_request.shares,
+                payout,
+                fee: self.withdraw_request.fee,
+            })
+        };
+
+        let amount = self.withdraw_request.payout;
+        let transferred = check_transfer_instruction(
+            &self.instructions,
+            1,
+            Some(self.vault_token_account.key()),
+            None,
+            self.user.key(),
+        )?;
+        require_eq!(transferred, amount);
 
         let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[self.vault.bump]]];
 
@@ -59,32 +232,24 @@ impl<'info> Withdraw<'info> {
             amount,
         )?;
 
-        match self.user_account.balance.checked_sub(amount) {
-            Some(x) => self.user_account.balance = x,
-            None => {} // add error
-        }
-
-        Ok(())
-    }
-
-    pub fn check_transfer_instruction(&self, amount: u64) -> Result<()> {
-        // instruction introspection
-        let current_index =
-            load_current_index_checked(&self.instructions.to_account_info())? as usize;
-        let ix =
-            load_instruction_at_checked(current_index + 1, &self.instructions.to_account_info())?;
-
-        // check below ix is from token22 program and transferchecked
-        require_keys_eq!(ix.program_id, anchor_spl::token_2022::ID);
-        require_eq!(ix.data.split_first().unwrap().0, &12);
-
-        let authority = ix.accounts.get(4).unwrap();
-        require_keys_eq!(authority.pubkey, self.user.key());
-
-        let amount_bytes = &ix.data[1..9];
-        let amount_ix = u64::from_le_bytes(amount_bytes.try_into().unwrap());
-        require_eq!(amount_ix, amount);
-
-        Ok(())
+        self.vault.total_claimable = self
+            .vault
+            .total_claimable
+            .checked_sub(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.vault.total_withdrawn = self
+            .vault
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok((
+            processed,
+            Withdrawn {
+                user: self.user.key(),
+                index,
+                amount,
+            },
+        ))
     }
 }
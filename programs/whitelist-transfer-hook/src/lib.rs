@@ -3,15 +3,20 @@
 
 use anchor_lang::prelude::*;
 
+mod error;
+mod events;
 mod instructions;
 mod state;
 mod tests;
 
 use instructions::*;
+use state::PolicyRules;
 
 use spl_discriminator::SplDiscriminate;
-use spl_tlv_account_resolution::state::ExtraAccountMetaList;
-use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use spl_transfer_hook_interface::instruction::{
+    ExecuteInstruction, InitializeExtraAccountMetaListInstruction,
+    UpdateExtraAccountMetaListInstruction,
+};
 
 declare_id!("DhzyDgCmmQzVC4vEcj2zRGUyN8Mt5JynfdGLKkBcRGaX");
 
@@ -20,57 +25,329 @@ pub mod whitelist_transfer_hook {
     use super::*;
 
     pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
-        ctx.accounts.initialize_vault(ctx.bumps)
+        let event = ctx.accounts.initialize_vault(&ctx.bumps)?;
+        emit_cpi!(event);
+
+        Ok(())
     }
 
-    pub fn mint_token(
+    pub fn create_mint(
         ctx: Context<TokenFactory>,
+        decimals: u8,
+        max_supply: u64,
         name: String,
         symbol: String,
         uri: String,
-        amount: u64,
+        extensions: MintExtensions,
+    ) -> Result<()> {
+        let event = ctx.accounts.create_mint(
+            decimals, max_supply, name, symbol, uri, extensions, &ctx.bumps,
+        )?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn mint_to_whitelisted(ctx: Context<MintToWhitelisted>, amount: u64) -> Result<()> {
+        let event = ctx.accounts.mint_to_whitelisted(amount)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn clawback(ctx: Context<Clawback>, amount: u64, reason: u16) -> Result<()> {
+        let event = ctx.accounts.clawback(amount, reason)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn pause_mint(ctx: Context<SetMintPause>) -> Result<()> {
+        let event = ctx.accounts.pause_mint()?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn resume_mint(ctx: Context<SetMintPause>) -> Result<()> {
+        let event = ctx.accounts.resume_mint()?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn harvest_transfer_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, HarvestTransferFees<'info>>,
+    ) -> Result<()> {
+        let event = ctx.accounts.harvest_transfer_fees(ctx.remaining_accounts)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn withdraw_transfer_fees(ctx: Context<WithdrawTransferFees>) -> Result<()> {
+        let event = ctx.accounts.withdraw_transfer_fees()?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn update_interest_rate(ctx: Context<UpdateUiAmount>, rate: i16) -> Result<()> {
+        let event = ctx.accounts.update_interest_rate(rate)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn update_ui_multiplier(
+        ctx: Context<UpdateUiAmount>,
+        multiplier: f64,
+        effective_timestamp: i64,
     ) -> Result<()> {
-        ctx.accounts.init_mint(name, symbol, uri, amount)
+        let event = ctx
+            .accounts
+            .update_ui_multiplier(multiplier, effective_timestamp)?;
+        emit_cpi!(event);
+
+        Ok(())
     }
 
-    pub fn add_to_whitelist(ctx: Context<AddToWhitelist>, user: Pubkey) -> Result<()> {
-        ctx.accounts.add_to_whitelist(user, &ctx.bumps)
+    pub fn update_metadata_field(
+        ctx: Context<UpdateTokenMetadata>,
+        field: MetadataField,
+        value: String,
+    ) -> Result<()> {
+        let event = ctx.accounts.update_metadata_field(field, value)?;
+        emit_cpi!(event);
+
+        Ok(())
     }
 
-    pub fn remove_from_whitelist(ctx: Context<RemoveFromWhitelist>, user: Pubkey) -> Result<()> {
-        ctx.accounts.remove_from_whitelist(user)
+    pub fn remove_metadata_key(ctx: Context<UpdateTokenMetadata>, key: String) -> Result<()> {
+        let event = ctx.accounts.remove_metadata_key(key)?;
+        emit_cpi!(event);
+
+        Ok(())
     }
 
-    pub fn deposit(ctx: Context<Deposit>) -> Result<()> {
-        ctx.accounts.deposit()
+    pub fn update_metadata_authority(
+        ctx: Context<UpdateTokenMetadata>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        let event = ctx.accounts.update_metadata_authority(new_authority)?;
+        emit_cpi!(event);
+
+        Ok(())
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-        ctx.accounts.withdraw(amount)
+    pub fn add_to_whitelist(
+        ctx: Context<AddToWhitelist>,
+        user: Pubkey,
+        jurisdiction: [u8; 2],
+    ) -> Result<()> {
+        let event = ctx
+            .accounts
+            .add_to_whitelist(user, jurisdiction, &ctx.bumps)?;
+        emit_cpi!(event);
+
+        Ok(())
     }
 
-    pub fn initialize_transfer_hook(ctx: Context<InitializeExtraAccountMetaList>) -> Result<()> {
+    pub fn set_jurisdiction(
+        ctx: Context<SetJurisdiction>,
+        user: Pubkey,
+        jurisdiction: [u8; 2],
+    ) -> Result<()> {
+        let event = ctx.accounts.set_jurisdiction(user, jurisdiction)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn set_max_holding(
+        ctx: Context<SetMaxHolding>,
+        user: Pubkey,
+        max_holding: Option<u64>,
+    ) -> Result<()> {
+        let event = ctx.accounts.set_max_holding(user, max_holding)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn remove_from_whitelist<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveFromWhitelist<'info>>,
+        user: Pubkey,
+    ) -> Result<()> {
+        let event = ctx
+            .accounts
+            .remove_from_whitelist(user, ctx.remaining_accounts)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn reinstate_user(ctx: Context<ReinstateUser>, user: Pubkey) -> Result<()> {
+        let event = ctx.accounts.reinstate_user(user)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, lock_duration: i64) -> Result<()> {
+        let event = ctx.accounts.deposit(lock_duration)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn request_withdraw(ctx: Context<RequestWithdraw>, amount: u64, index: u64) -> Result<()> {
+        let event = ctx.accounts.request_withdraw(amount, index, &ctx.bumps)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn process_withdrawals<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessWithdrawals<'info>>,
+    ) -> Result<()> {
+        let events = ctx.accounts.process_withdrawals(ctx.remaining_accounts)?;
+        for event in events {
+            emit_cpi!(event);
+        }
+
+        Ok(())
+    }
+
+    pub fn claim_withdraw(ctx: Context<ClaimWithdraw>, index: u64) -> Result<()> {
+        let (processed, event) = ctx.accounts.claim_withdraw(index)?;
+        if let Some(processed) = processed {
+            emit_cpi!(processed);
+        }
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn set_withdraw_cooldown(
+        ctx: Context<UpdateVaultConfig>,
+        withdraw_cooldown: i64,
+    ) -> Result<()> {
+        let event = ctx.accounts.set_withdraw_cooldown(withdraw_cooldown)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn set_fees(
+        ctx: Context<UpdateVaultConfig>,
+        deposit_fee_bps: u16,
+        withdraw_fee_bps: u16,
+    ) -> Result<()> {
+        let event = ctx.accounts.set_fees(deposit_fee_bps, withdraw_fee_bps)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn set_treasury(ctx: Context<SetTreasury>) -> Result<()> {
+        let event = ctx.accounts.set_treasury()?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+        let event = ctx.accounts.collect_fees()?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn distribute_yield(ctx: Context<DistributeYield>) -> Result<()> {
+        let event = ctx.accounts.distribute_yield()?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        let event = ctx.accounts.reconcile()?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn sweep_surplus(ctx: Context<SweepSurplus>, beneficiary: Option<Pubkey>) -> Result<()> {
+        let event = ctx.accounts.sweep_surplus(beneficiary)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn initialize_mint_policy(
+        ctx: Context<InitializeMintPolicy>,
+        rules: PolicyRules,
+    ) -> Result<()> {
+        let event = ctx.accounts.initialize_mint_policy(rules, &ctx.bumps)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    pub fn update_mint_policy(ctx: Context<UpdateMintPolicy>, rules: PolicyRules) -> Result<()> {
+        let event = ctx.accounts.update_mint_policy(rules)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    #[instruction(discriminator = InitializeExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn initialize_transfer_hook(
+        ctx: Context<InitializeExtraAccountMetaList>,
+        extra_account_metas: Vec<ExtraAccountMetaData>,
+    ) -> Result<()> {
         msg!("Initializing Transfer Hook...");
 
         // Get the extra account metas for the transfer hook
-        let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas()?;
+        let extra_account_metas = resolve_extra_account_metas(&extra_account_metas)?;
 
         msg!("Extra Account Metas: {:?}", extra_account_metas);
         msg!("Extra Account Metas Length: {}", extra_account_metas.len());
 
         // initialize ExtraAccountMetaList account with extra accounts
-        ExtraAccountMetaList::init::<ExecuteInstruction>(
-            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
-            &extra_account_metas,
-        )
-        .unwrap();
+        ctx.accounts
+            .initialize_extra_account_meta_list(&extra_account_metas, &ctx.bumps)
+    }
 
-        Ok(())
+    #[instruction(discriminator = UpdateExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn update_transfer_hook(
+        ctx: Context<UpdateExtraAccountMetaList>,
+        extra_account_metas: Vec<ExtraAccountMetaData>,
+    ) -> Result<()> {
+        let extra_account_metas = resolve_extra_account_metas(&extra_account_metas)?;
+
+        ctx.accounts
+            .update_extra_account_meta_list(&extra_account_metas)
     }
 
     #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
     pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
         // Call the transfer hook logic
-        ctx.accounts.transfer_hook(amount)
+        match ctx.accounts.transfer_hook(amount)? {
+            TransferOutcome::Allowed(event) => emit_cpi!(event),
+            TransferOutcome::Flagged(event, violation) => {
+                emit_cpi!(violation);
+                emit_cpi!(event);
+            }
+            TransferOutcome::Rejected(event) => {
+                // The failing transfer reverts the self-CPI too, only the log line survives
+                let error = event.reason.error();
+                emit!(event);
+                return Err(error.into());
+            }
+        }
+
+        Ok(())
     }
 }
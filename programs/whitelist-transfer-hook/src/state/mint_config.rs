@@ -0,0 +1,12 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MintConfig {
+    pub mint: Pubkey,
+    /// Authorized supply, no mint path may issue beyond it
+    pub max_supply: u64,
+    /// Running total of every issuance through the program
+    pub issued: u64,
+    pub bump: u8,
+}
@@ -0,0 +1,197 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    state::{validate_jurisdiction, User},
+};
+
+pub const MAX_BLACKOUTS: usize = 8;
+pub const MAX_JURISDICTION_PAIRS: usize = 32;
+
+/// Every hour of the day
+pub const ALL_HOURS: u32 = (1 << 24) - 1;
+/// Every day of the week
+pub const ALL_WEEKDAYS: u8 = (1 << 7) - 1;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum EnforcementMode {
+    /// Every transfer passes unchecked
+    Off,
+    /// Violating transfers pass but emit `PolicyViolation`, to preview a rule before enforcing it
+    Monitor,
+    /// Violating transfers are rejected
+    Enforce,
+}
+
+/// Rules the transfer hook applies to every transfer of the mint. Unless the mode is `Off` the
+/// transfer authority must always be whitelisted, the rest decide what else is checked.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct PolicyRules {
+    pub mode: EnforcementMode,
+    /// Owner that needs no whitelist entry on either side of a transfer, normally the vault
+    pub exempt_owner: Pubkey,
+    /// Source token account owner must be whitelisted, not just whoever signs for it
+    pub check_source_owner: bool,
+    /// Destination token account owner must be whitelisted
+    pub check_destination_owner: bool,
+    pub trading_window: TradingWindow,
+    /// Source and destination owners must both have a jurisdiction pair in `allowed_pairs`.
+    /// The exempt owner has no jurisdiction and is never checked.
+    pub check_jurisdictions: bool,
+    #[max_len(MAX_JURISDICTION_PAIRS)]
+    pub allowed_pairs: Vec<JurisdictionPair>,
+    /// Most tokens a destination owner may hold after a transfer, unless its `User` sets its own.
+    /// Checked on the owner's associated token account, the only one it may then receive into.
+    pub max_holding: Option<u64>,
+}
+
+impl Default for PolicyRules {
+    /// Rules of a mint without a `MintPolicy`: enforced, trading around the clock, both owners
+    /// whitelisted and only the vault exempt.
+    fn default() -> Self {
+        Self {
+            mode: EnforcementMode::Enforce,
+            exempt_owner: Pubkey::find_program_address(&[b"vault"], &crate::ID).0,
+            check_source_owner: true,
+            check_destination_owner: true,
+            trading_window: TradingWindow {
+                hours: ALL_HOURS,
+                weekdays: ALL_WEEKDAYS,
+                blackouts: vec![],
+            },
+            check_jurisdictions: false,
+            allowed_pairs: vec![],
+            max_holding: None,
+        }
+    }
+}
+
+/// Holders in `from` may send to holders in `to`, add both directions for two-way trading
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct JurisdictionPair {
+    pub from: [u8; 2],
+    pub to: [u8; 2],
+}
+
+impl PolicyRules {
+    pub fn validate(&self) -> Result<()> {
+        self.trading_window.validate()?;
+
+        require_gte!(
+            MAX_JURISDICTION_PAIRS,
+            self.allowed_pairs.len(),
+            ErrorCode::TooManyJurisdictionPairs
+        );
+        for pair in &self.allowed_pairs {
+            validate_jurisdiction(&pair.from)?;
+            validate_jurisdiction(&pair.to)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn allows_jurisdictions(&self, from: [u8; 2], to: [u8; 2]) -> bool {
+        self.allowed_pairs
+            .iter()
+            .any(|pair| pair.from == from && pair.to == to)
+    }
+
+    /// Balance limit for a holder, its own if set, otherwise the mint-wide one.
+    pub fn holding_limit(&self, user: Option<&User>) -> Option<u64> {
+        user.and_then(|user| user.max_holding).or(self.max_holding)
+    }
+
+    /// Rejects a holder ending up with `amount` in an account, `associated` if it is the
+    /// holder's associated token account, as the hook would after a transfer into it. For
+    /// tokens that arrive without passing through the hook; only an enforced policy rejects.
+    pub fn check_holding(&self, user: &User, associated: bool, amount: u64) -> Result<()> {
+        if self.mode != EnforcementMode::Enforce {
+            return Ok(());
+        }
+
+        if let Some(max_holding) = self.holding_limit(Some(user)) {
+            require!(associated, ErrorCode::HoldingOutsideAssociatedAccount);
+            require_gte!(max_holding, amount, ErrorCode::MaxHoldingExceeded);
+        }
+
+        Ok(())
+    }
+}
+
+/// When transfers may happen, all times in UTC. Transfers must fall in an allowed hour on an
+/// allowed weekday and outside every blackout.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct TradingWindow {
+    /// Bit `n` allows hour `n`, `ALL_HOURS` trades around the clock
+    pub hours: u32,
+    /// Bit 0 allows Monday through bit 6 for Sunday, `ALL_WEEKDAYS` trades every day
+    pub weekdays: u8,
+    /// E.g. around earnings announcements
+    #[max_len(MAX_BLACKOUTS)]
+    pub blackouts: Vec<Blackout>,
+}
+
+/// Closed period from `start_ts` up to but excluding `end_ts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Blackout {
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+impl TradingWindow {
+    pub fn validate(&self) -> Result<()> {
+        require_gte!(ALL_HOURS, self.hours, ErrorCode::InvalidTradingWindow);
+        require_gte!(ALL_WEEKDAYS, self.weekdays, ErrorCode::InvalidTradingWindow);
+        require_gte!(
+            MAX_BLACKOUTS,
+            self.blackouts.len(),
+            ErrorCode::InvalidTradingWindow
+        );
+        for blackout in &self.blackouts {
+            require_gt!(
+                blackout.end_ts,
+                blackout.start_ts,
+                ErrorCode::InvalidTradingWindow
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn is_open(&self, now: i64) -> bool {
+        let days = now.div_euclid(SECONDS_PER_DAY);
+        let hour = now.rem_euclid(SECONDS_PER_DAY) / 3_600;
+        // 1970-01-01 was a Thursday
+        let weekday = (days + 3).rem_euclid(7);
+
+        self.hours & (1 << hour) != 0
+            && self.weekdays & (1 << weekday) != 0
+            && !self
+                .blackouts
+                .iter()
+                .any(|blackout| blackout.start_ts <= now && now < blackout.end_ts)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MintPolicy {
+    pub mint: Pubkey,
+    pub rules: PolicyRules,
+    pub bump: u8,
+}
+
+impl MintPolicy {
+    /// Rules in the mint's policy PDA, or [`PolicyRules::default`] if no policy was initialized.
+    pub fn load_rules(mint_policy: &AccountInfo) -> Result<PolicyRules> {
+        if mint_policy.owner != &crate::ID {
+            return Ok(PolicyRules::default());
+        }
+
+        let data = mint_policy.try_borrow_data()?;
+
+        Ok(Self::try_deserialize(&mut &data[..])?.rules)
+    }
+}
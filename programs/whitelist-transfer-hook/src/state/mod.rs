@@ -1,5 +1,13 @@
+pub mod mint_config;
+pub mod mint_policy;
+pub mod transfer_log;
 pub mod user;
 pub mod vault;
+pub mod withdraw_request;
 
+pub use mint_config::*;
+pub use mint_policy::*;
+pub use transfer_log::*;
 pub use user::*;
 pub use vault::*;
+pub use withdraw_request::*;
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+pub const TRANSFER_LOG_CAPACITY: usize = 64;
+
+#[account(zero_copy)]
+pub struct TransferLog {
+    pub mint: Pubkey,
+    /// Number of transfers ever recorded, the next write goes to `total % TRANSFER_LOG_CAPACITY`
+    pub total: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub records: [TransferRecord; TRANSFER_LOG_CAPACITY],
+}
+
+#[zero_copy]
+pub struct TransferRecord {
+    pub slot: u64,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub amount: u64,
+}
+
+impl TransferLog {
+    /// Overwrites the oldest record once the buffer is full.
+    pub fn append(&mut self, record: TransferRecord) {
+        let index = (self.total % TRANSFER_LOG_CAPACITY as u64) as usize;
+        self.records[index] = record;
+        self.total = self.total.wrapping_add(1);
+    }
+}
@@ -1,8 +1,60 @@
 use anchor_lang::prelude::*;
 
+use crate::error::ErrorCode;
+
+/// Replaces the original `User { balance, bump }` without a migration path: entries and the vault
+/// created by earlier deployments cannot be read by this layout and need a fresh deployment.
 #[account]
 #[derive(InitSpace)]
 pub struct User {
-    pub balance: u64,
+    /// Vault shares, redeemable at the vault's current price per share
+    pub shares: u64,
+    /// Term deposits that may not be withdrawn before `unlock_ts`
+    #[max_len(MAX_LOCKS)]
+    pub locks: Vec<Lock>,
     pub bump: u8,
+    /// ISO 3166-1 alpha-2 country code, e.g. `*b"US"`
+    pub jurisdiction: [u8; 2],
+    /// Overrides the mint policy's `max_holding` for this wallet
+    pub max_holding: Option<u64>,
+    /// Removed from the whitelist while still holding shares, treated as not whitelisted
+    pub suspended: bool,
+}
+
+pub const MAX_LOCKS: usize = 8;
+
+/// Jurisdictions are stored as two uppercase ASCII letters.
+pub fn validate_jurisdiction(jurisdiction: &[u8; 2]) -> Result<()> {
+    require!(
+        jurisdiction.iter().all(u8::is_ascii_uppercase),
+        ErrorCode::InvalidJurisdiction
+    );
+
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Lock {
+    pub shares: u64,
+    pub unlock_ts: i64,
+}
+
+impl User {
+    /// Drops locks that have matured by `now`.
+    pub fn prune_locks(&mut self, now: i64) {
+        self.locks.retain(|lock| lock.unlock_ts > now);
+    }
+
+    /// Shares still locked at `now`.
+    pub fn locked_shares(&self, now: i64) -> u64 {
+        self.locks
+            .iter()
+            .filter(|lock| lock.unlock_ts > now)
+            .fold(0u64, |total, lock| total.saturating_add(lock.shares))
+    }
+
+    /// Shares that can be withdrawn at `now`.
+    pub fn unlocked_shares(&self, now: i64) -> u64 {
+        self.shares.saturating_sub(self.locked_shares(now))
+    }
 }
@@ -1,10 +1,96 @@
 use anchor_lang::prelude::*;
 
+/// Grew from the original admin, mint, token account and bump by the totals, fees and treasury
+/// below. Vaults of earlier deployments are not migrated, see [`crate::state::User`].
 #[account]
 #[derive(InitSpace)]
 pub struct Vault {
     pub admin: Pubkey,
     pub mint: Pubkey,
     pub vault_token_account: Pubkey,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    /// Tokens owed to depositors, deposits plus distributed yield minus withdrawals
+    pub total_outstanding: u64,
+    pub total_shares: u64,
+    /// Processed withdraw requests not yet claimed
+    pub total_claimable: u64,
+    pub user_count: u64,
+    /// Seconds between `request_withdraw` and the earliest claim
+    pub withdraw_cooldown: i64,
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+    /// Token account `collect_fees` pays out to
+    pub treasury: Pubkey,
+    /// Fees charged against the ledger and not yet collected
+    pub accrued_fees: u64,
     pub bump: u8,
 }
+
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+impl Vault {
+    /// Tokens the vault token account must hold to cover every depositor, pending claim and
+    /// uncollected fee.
+    pub fn liabilities(&self) -> Option<u64> {
+        self.total_outstanding
+            .checked_add(self.total_claimable)?
+            .checked_add(self.accrued_fees)
+    }
+
+    pub fn deposit_fee(&self, amount: u64) -> Option<u64> {
+        fee_for(amount, self.deposit_fee_bps)
+    }
+
+    pub fn withdraw_fee(&self, amount: u64) -> Option<u64> {
+        fee_for(amount, self.withdraw_fee_bps)
+    }
+
+    /// Shares minted for a deposit of `amount`, rounded down in favour of the vault.
+    pub fn shares_for_deposit(&self, amount: u64) -> Option<u64> {
+        if self.total_shares == 0 || self.total_outstanding == 0 {
+            return Some(amount);
+        }
+
+        let shares = (amount as u128)
+            .checked_mul(self.total_shares as u128)?
+            .checked_div(self.total_outstanding as u128)?;
+
+        u64::try_from(shares).ok()
+    }
+
+    /// Shares burned to withdraw `amount`, rounded up in favour of the vault.
+    pub fn shares_for_withdraw(&self, amount: u64) -> Option<u64> {
+        if self.total_outstanding == 0 {
+            return None;
+        }
+
+        let shares = (amount as u128)
+            .checked_mul(self.total_shares as u128)?
+            .checked_add(self.total_outstanding as u128 - 1)?
+            .checked_div(self.total_outstanding as u128)?;
+
+        u64::try_from(shares).ok()
+    }
+
+    /// Tokens the given shares are currently worth, rounded down.
+    pub fn assets_for_shares(&self, shares: u64) -> Option<u64> {
+        if self.total_shares == 0 {
+            return Some(0);
+        }
+
+        let assets = (shares as u128)
+            .checked_mul(self.total_outstanding as u128)?
+            .checked_div(self.total_shares as u128)?;
+
+        u64::try_from(assets).ok()
+    }
+}
+
+fn fee_for(amount: u64, fee_bps: u16) -> Option<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)?
+        .checked_div(MAX_FEE_BPS as u128)?;
+
+    u64::try_from(fee).ok()
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, state::Vault};
+
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawRequest {
+    pub user: Pubkey,
+    /// Seed chosen by the user, unique among their pending requests
+    pub index: u64,
+    /// Tokens requested, the payout is whatever `shares` are worth once processed
+    pub amount: u64,
+    /// Shares escrowed out of `User.shares` until the request is processed
+    pub shares: u64,
+    pub unlock_ts: i64,
+    /// Tokens owed to the user after the withdraw fee, fixed when the request is processed
+    pub payout: u64,
+    pub fee: u64,
+    pub processed: bool,
+    pub bump: u8,
+}
+
+impl WithdrawRequest {
+    pub fn is_matured(&self, now: i64) -> bool {
+        now >= self.unlock_ts
+    }
+
+    /// Burns the escrowed shares at the current price per share and moves their value, less
+    /// the withdraw fee, from the outstanding ledger into the claimable total.
+    pub fn process(&mut self, vault: &mut Vault) -> Result<u64> {
+        let value = vault
+            .assets_for_shares(self.shares)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let fee = vault.withdraw_fee(value).ok_or(ErrorCode::MathOverflow)?;
+        let payout = value.checked_sub(fee).ok_or(ErrorCode::MathOverflow)?;
+
+        vault.total_shares = vault
+            .total_shares
+            .checked_sub(self.shares)
+            .ok_or(ErrorCode::MathOverflow)?;
+        vault.total_outstanding = vault
+            .total_outstanding
+            .checked_sub(value)
+            .ok_or(ErrorCode::MathOverflow)?;
+        vault.accrued_fees = vault
+            .accrued_fees
+            .checked_add(fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+        vault.total_claimable = vault
+            .total_claimable
+            .checked_add(payout)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        self.payout = payout;
+        self.fee = fee;
+        self.processed = true;
+
+        Ok(payout)
+    }
+}
@@ -0,0 +1,106 @@
+use {solana_instruction::Instruction, solana_pubkey::Pubkey, solana_signer::Signer};
+
+use super::harness::*;
+use crate::{
+    error::ErrorCode,
+    events::TokensClawedBack,
+    state::{PolicyRules, TransferLog, TRANSFER_LOG_CAPACITY},
+    MintExtensions,
+};
+
+fn clawback_ix(harness: &Harness, from: &Pubkey, to: &Pubkey, amount: u64) -> Instruction {
+    harness.ix(
+        crate::accounts::Clawback {
+            admin: harness.admin.pubkey(),
+            vault: vault(),
+            mint: harness.mint,
+            source: harness.ata(from),
+            destination: harness.ata(to),
+            destination_state: user_pda(to),
+            mint_policy: harness.mint_policy(),
+            transfer_log: harness.transfer_log(),
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::Clawback { amount, reason: 7 },
+    )
+}
+
+#[tokio::test]
+async fn test_clawback_is_logged_and_blocked_by_pause() {
+    let mut harness = Harness::new(MintExtensions {
+        pausable: true,
+        ..Default::default()
+    })
+    .await;
+    let holder = harness.new_user(*b"US").await;
+    let recipient = harness.new_user(*b"DE").await;
+    harness.mint_to(&holder.pubkey(), 1_000).await;
+    harness.mint_to(&recipient.pubkey(), 1).await;
+
+    let transfer_log: TransferLog = harness.account(&harness.transfer_log()).await;
+    let total = transfer_log.total;
+
+    // burning and minting are halted along with transfers
+    let ix = harness.pause_ix(crate::instruction::PauseMint {});
+    harness.send(&[ix], &[]).await.unwrap();
+    let ix = clawback_ix(&harness, &holder.pubkey(), &recipient.pubkey(), 500);
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        spl_token_2022::error::TokenError::MintPaused as u32,
+    );
+
+    let ix = harness.pause_ix(crate::instruction::ResumeMint {});
+    harness.send(&[ix], &[]).await.unwrap();
+    let ix = clawback_ix(&harness, &holder.pubkey(), &recipient.pubkey(), 400);
+    let logs = harness.send(&[ix], &[]).await.unwrap();
+
+    let clawed_back = events::<TokensClawedBack>(&logs);
+    assert_eq!(clawed_back[0].amount, 400);
+    assert_eq!(clawed_back[0].reason, 7);
+    assert_eq!(harness.token_amount(&holder.pubkey()).await, 600);
+    assert_eq!(harness.token_amount(&recipient.pubkey()).await, 401);
+
+    // the hook never ran, the seizure is still on record
+    let transfer_log: TransferLog = harness.account(&harness.transfer_log()).await;
+    assert_eq!(transfer_log.total, total + 1);
+    let record = transfer_log.records[(total as usize) % TRANSFER_LOG_CAPACITY];
+    assert_eq!(record.source_owner, holder.pubkey());
+    assert_eq!(record.destination_owner, recipient.pubkey());
+    assert_eq!(record.amount, 400);
+}
+
+#[tokio::test]
+async fn test_minting_and_clawback_respect_holding_limit() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let holder = harness.new_user(*b"US").await;
+    let recipient = harness.new_user(*b"DE").await;
+    harness.mint_to(&holder.pubkey(), 1_000).await;
+    harness.mint_to(&recipient.pubkey(), 600).await;
+    harness
+        .set_policy(PolicyRules {
+            max_holding: Some(1_000),
+            ..default_rules()
+        })
+        .await;
+
+    // new supply is held to the limit like a transfer would be
+    let ix = harness.mint_to_ix(&recipient.pubkey(), 401);
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        ErrorCode::MaxHoldingExceeded,
+    );
+    harness.mint_to(&recipient.pubkey(), 300).await;
+
+    let ix = clawback_ix(&harness, &holder.pubkey(), &recipient.pubkey(), 101);
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        ErrorCode::MaxHoldingExceeded,
+    );
+    let ix = clawback_ix(&harness, &holder.pubkey(), &recipient.pubkey(), 100);
+    harness.send(&[ix], &[]).await.unwrap();
+
+    assert_eq!(harness.token_amount(&holder.pubkey()).await, 900);
+    assert_eq!(harness.token_amount(&recipient.pubkey()).await, 1_000);
+}
@@ -0,0 +1,643 @@
+//! Runs the program natively through `solana-program-test`, against its bundled Token-2022 build.
+//! Without a deployed `.so` the program cannot be loaded into LiteSVM.
+#![allow(dead_code)]
+
+use {
+    anchor_lang::{
+        prelude::{AccountInfo, Clock},
+        solana_program::{entrypoint::ProgramResult, program_stubs},
+        AccountDeserialize, AnchorDeserialize, Discriminator, InstructionData, ToAccountMetas,
+    },
+    anchor_spl::associated_token,
+    solana_instruction::{error::InstructionError, AccountMeta, Instruction},
+    solana_keypair::Keypair,
+    solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext},
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::system_program::ID as SYSTEM_PROGRAM_ID,
+    solana_signer::Signer,
+    solana_transaction::Transaction,
+    solana_transaction_error::TransactionError,
+    spl_tlv_account_resolution::state::ExtraAccountMetaList,
+    spl_token_2022::{
+        extension::{ExtensionType, StateWithExtensions},
+        instruction::initialize_account3,
+        state::Account as TokenAccountState,
+    },
+    spl_transfer_hook_interface::instruction::initialize_extra_account_meta_list,
+};
+
+use crate::{state::PolicyRules, MintExtensions};
+
+pub const PROGRAM_ID: Pubkey = crate::ID;
+pub const TOKEN_PROGRAM_ID: Pubkey = spl_token_2022::ID;
+pub const DECIMALS: u8 = 6;
+pub const MAX_SUPPLY: u64 = 1_000_000_000_000;
+
+/// Event CPIs are logged before dispatch, the banks client only returns log messages.
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    if let Some(event) = data.strip_prefix(anchor_lang::event::EVENT_IX_TAG_LE) {
+        let hex: String = event.iter().map(|byte| format!("{byte:02x}")).collect();
+        // `msg!` prints to stdout off-chain, the stub records it in the transaction logs
+        program_stubs::sol_log(&format!("event {hex}"));
+    }
+
+    // The entrypoint ties the account infos to the accounts slice lifetime
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    crate::entry(program_id, accounts, data)
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID).0
+}
+
+pub fn event_authority() -> Pubkey {
+    pda(&[b"__event_authority"])
+}
+
+pub fn vault() -> Pubkey {
+    pda(&[b"vault"])
+}
+
+pub fn user_pda(wallet: &Pubkey) -> Pubkey {
+    pda(&[b"user", wallet.as_ref()])
+}
+
+/// Enforced rules with the vault exempt and every other check off
+pub fn default_rules() -> PolicyRules {
+    PolicyRules::default()
+}
+
+/// Asserts that `result` failed with `error`, a program or Anchor error code.
+pub fn assert_error<T, E>(result: Result<T, BanksClientError>, error: E)
+where
+    T: std::fmt::Debug,
+    E: Into<u32> + std::fmt::Debug + Copy,
+{
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(actual),
+        ))) => assert_eq!(actual, error.into(), "expected {error:?}"),
+        other => panic!("expected {error:?}, got {other:?}"),
+    }
+}
+
+/// Decodes every event of type `T` logged by [`process_instruction`].
+pub fn events<T: AnchorDeserialize + Discriminator>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix("Program log: event "))
+        .map(|hex| {
+            (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                .collect::<Vec<u8>>()
+        })
+        .filter_map(|data| {
+            data.strip_prefix(T::DISCRIMINATOR)
+                .map(|event| T::deserialize(&mut &event[..]).unwrap())
+        })
+        .collect()
+}
+
+/// The transfer-hook interface crate builds on the newer SDK types
+pub fn address(pubkey: &Pubkey) -> solana_address::Address {
+    solana_address::Address::new_from_array(pubkey.to_bytes())
+}
+
+pub fn interface_ix(ix: solana_instruction_v3::Instruction) -> Instruction {
+    Instruction {
+        program_id: Pubkey::new_from_array(ix.program_id.to_bytes()),
+        accounts: ix
+            .accounts
+            .into_iter()
+            .map(|meta| AccountMeta {
+                pubkey: Pubkey::new_from_array(meta.pubkey.to_bytes()),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: ix.data,
+    }
+}
+
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,
+    pub mint: Pubkey,
+}
+
+impl Harness {
+    /// Creates a mint with `extensions`, its hook accounts, the vault and a policy with
+    /// [`default_rules`].
+    pub async fn new(extensions: MintExtensions) -> Self {
+        let mut program_test = ProgramTest::new(
+            "whitelist_transfer_hook",
+            PROGRAM_ID,
+            processor!(process_instruction),
+        );
+        program_test.prefer_bpf(false);
+
+        let context = program_test.start_with_context().await;
+        let admin = context.payer.insecure_clone();
+        let mint = Keypair::new();
+
+        let mut harness = Self {
+            context,
+            admin,
+            mint: mint.pubkey(),
+        };
+
+        let admin = harness.admin.pubkey();
+        let [fund_meta_list, initialize_meta_list] = harness
+            .initialize_meta_list_ixs(&admin, &mint.pubkey())
+            .await;
+        let ixs = vec![
+            harness.create_mint_ix(&admin, &harness.mint, extensions),
+            fund_meta_list,
+            initialize_meta_list,
+            harness.ix(
+                crate::accounts::InitializeVault {
+                    admin,
+                    vault: vault(),
+                    mint: harness.mint,
+                    vault_token_account: harness.ata(&vault()),
+                    associated_token_program: associated_token::ID,
+                    token_program: TOKEN_PROGRAM_ID,
+                    system_program: SYSTEM_PROGRAM_ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
+                },
+                crate::instruction::InitializeVault {},
+            ),
+            harness.initialize_policy_ix(default_rules()),
+        ];
+
+        harness.send(&ixs, &[&mint]).await.unwrap();
+
+        harness
+    }
+
+    pub fn ix(&self, accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: PROGRAM_ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    /// `create_mint` for `mint` with the same parameters as the harness mint
+    pub fn create_mint_ix(
+        &self,
+        admin: &Pubkey,
+        mint: &Pubkey,
+        extensions: MintExtensions,
+    ) -> Instruction {
+        self.ix(
+            crate::accounts::TokenFactory {
+                admin: *admin,
+                vault: vault(),
+                mint: *mint,
+                mint_config: pda(&[b"mint-config", mint.as_ref()]),
+                transfer_log: pda(&[b"transfer-log", mint.as_ref()]),
+                system_program: SYSTEM_PROGRAM_ID,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::CreateMint {
+                decimals: DECIMALS,
+                max_supply: MAX_SUPPLY,
+                name: "Vault Token".to_string(),
+                symbol: "VLT".to_string(),
+                uri: "https://example.com/vlt.json".to_string(),
+                extensions,
+            },
+        )
+    }
+
+    /// Funds the extra account meta list of `mint` for rent and initializes it as any client of
+    /// the transfer-hook interface would, with `authority` as signer.
+    pub async fn initialize_meta_list_ixs(
+        &mut self,
+        authority: &Pubkey,
+        mint: &Pubkey,
+    ) -> [Instruction; 2] {
+        let extra_account_metas =
+            crate::InitializeExtraAccountMetaList::extra_account_metas().unwrap();
+        let meta_list = pda(&[b"extra-account-metas", mint.as_ref()]);
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let lamports =
+            rent.minimum_balance(ExtraAccountMetaList::size_of(extra_account_metas.len()).unwrap());
+
+        [
+            solana_system_interface::instruction::transfer(
+                &self.admin.pubkey(),
+                &meta_list,
+                lamports,
+            ),
+            interface_ix(initialize_extra_account_meta_list(
+                &address(&PROGRAM_ID),
+                &address(&meta_list),
+                &address(mint),
+                &address(authority),
+                &extra_account_metas,
+            )),
+        ]
+    }
+
+    /// Sends `ixs` paid by the admin, returning the transaction logs.
+    pub async fn send(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Vec<String>, BanksClientError> {
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        let mut all_signers = vec![&self.admin];
+        all_signers.extend_from_slice(signers);
+
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.admin.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+
+        let outcome = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(tx)
+            .await?;
+        outcome.result?;
+
+        Ok(outcome
+            .metadata
+            .map(|metadata| metadata.log_messages)
+            .unwrap_or_default())
+    }
+
+    pub fn meta_list(&self) -> Pubkey {
+        pda(&[b"extra-account-metas", self.mint.as_ref()])
+    }
+
+    pub fn transfer_log(&self) -> Pubkey {
+        pda(&[b"transfer-log", self.mint.as_ref()])
+    }
+
+    pub fn mint_config(&self) -> Pubkey {
+        pda(&[b"mint-config", self.mint.as_ref()])
+    }
+
+    pub fn mint_policy(&self) -> Pubkey {
+        pda(&[b"mint-policy", self.mint.as_ref()])
+    }
+
+    pub fn ata(&self, owner: &Pubkey) -> Pubkey {
+        associated_token::get_associated_token_address_with_program_id(
+            owner,
+            &self.mint,
+            &TOKEN_PROGRAM_ID,
+        )
+    }
+
+    /// Whitelists a new funded wallet in `jurisdiction`.
+    pub async fn new_user(&mut self, jurisdiction: [u8; 2]) -> Keypair {
+        let wallet = Keypair::new();
+        self.whitelist(&wallet.pubkey(), jurisdiction).await;
+        self.airdrop(&wallet.pubkey(), 1_000_000_000).await;
+
+        wallet
+    }
+
+    pub async fn whitelist(&mut self, wallet: &Pubkey, jurisdiction: [u8; 2]) {
+        let ix = self.ix(
+            crate::accounts::AddToWhitelist {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                user: user_pda(wallet),
+                wallet: *wallet,
+                mint: self.mint,
+                user_token_account: self.ata(wallet),
+                system_program: SYSTEM_PROGRAM_ID,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: associated_token::ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::AddToWhitelist {
+                user: *wallet,
+                jurisdiction,
+            },
+        );
+
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    /// `remove_from_whitelist` for `wallet`, freezing `user_token_account` if given
+    pub fn remove_ix(&self, wallet: &Pubkey, user_token_account: Option<Pubkey>) -> Instruction {
+        self.ix(
+            crate::accounts::RemoveFromWhitelist {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                user: user_pda(wallet),
+                mint: self.mint,
+                user_token_account,
+                system_program: SYSTEM_PROGRAM_ID,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::RemoveFromWhitelist { user: *wallet },
+        )
+    }
+
+    pub fn reinstate_ix(&self, wallet: &Pubkey) -> Instruction {
+        self.ix(
+            crate::accounts::ReinstateUser {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                user: user_pda(wallet),
+                mint: self.mint,
+                user_token_account: Some(self.ata(wallet)),
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::ReinstateUser { user: *wallet },
+        )
+    }
+
+    /// `sweep_surplus` to the whitelist entry of `beneficiary`, or to the treasury when `None`
+    pub fn sweep_ix(&self, beneficiary: Option<&Pubkey>) -> Instruction {
+        self.ix(
+            crate::accounts::SweepSurplus {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                vault_token_account: self.ata(&vault()),
+                beneficiary_state: beneficiary.map(user_pda),
+                instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::SweepSurplus {
+                beneficiary: beneficiary.copied(),
+            },
+        )
+    }
+
+    /// Points the vault treasury at the associated token account of `wallet`.
+    pub async fn set_treasury(&mut self, wallet: &Pubkey) {
+        let ix = self.ix(
+            crate::accounts::SetTreasury {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                mint: self.mint,
+                treasury: self.ata(wallet),
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::SetTreasury {},
+        );
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    pub fn initialize_policy_ix(&self, rules: PolicyRules) -> Instruction {
+        self.ix(
+            crate::accounts::InitializeMintPolicy {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                mint: self.mint,
+                mint_policy: self.mint_policy(),
+                system_program: SYSTEM_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::InitializeMintPolicy { rules },
+        )
+    }
+
+    /// Replaces the rules of the harness mint's policy.
+    pub async fn set_policy(&mut self, rules: PolicyRules) {
+        let ix = self.ix(
+            crate::accounts::UpdateMintPolicy {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                mint: self.mint,
+                mint_policy: self.mint_policy(),
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::UpdateMintPolicy { rules },
+        );
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    /// `pause_mint` or `resume_mint`, depending on `data`
+    pub fn pause_ix(&self, data: impl InstructionData) -> Instruction {
+        self.ix(
+            crate::accounts::SetMintPause {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                mint: self.mint,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            data,
+        )
+    }
+
+    pub fn mint_to_ix(&self, recipient: &Pubkey, amount: u64) -> Instruction {
+        self.ix(
+            crate::accounts::MintToWhitelisted {
+                admin: self.admin.pubkey(),
+                vault: vault(),
+                recipient: *recipient,
+                recipient_state: user_pda(recipient),
+                mint: self.mint,
+                mint_config: self.mint_config(),
+                mint_policy: self.mint_policy(),
+                recipient_ata: self.ata(recipient),
+                system_program: SYSTEM_PROGRAM_ID,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: associated_token::ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::MintToWhitelisted { amount },
+        )
+    }
+
+    pub async fn mint_to(&mut self, recipient: &Pubkey, amount: u64) {
+        let ix = self.mint_to_ix(recipient, amount);
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    /// Opens a token account for `owner` at a fresh keypair address instead of the ATA
+    pub async fn create_token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let account = Keypair::new();
+        let space = ExtensionType::try_calculate_account_len::<TokenAccountState>(&[
+            ExtensionType::TransferHookAccount,
+        ])
+        .unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+
+        let ixs = [
+            solana_system_interface::instruction::create_account(
+                &self.admin.pubkey(),
+                &account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &TOKEN_PROGRAM_ID,
+            ),
+            initialize_account3(&TOKEN_PROGRAM_ID, &account.pubkey(), &self.mint, owner).unwrap(),
+        ];
+        self.send(&ixs, &[&account]).await.unwrap();
+
+        account.pubkey()
+    }
+
+    pub async fn airdrop(&mut self, to: &Pubkey, lamports: u64) {
+        let ix = solana_system_interface::instruction::transfer(&self.admin.pubkey(), to, lamports);
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    /// `TransferChecked` from `source` to `destination` signed by `authority`, with the hook's
+    /// extra accounts resolved from the meta list.
+    pub async fn transfer_ix(
+        &mut self,
+        source: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let banks_client = self.context.banks_client.clone();
+
+        spl_token_2022::offchain::create_transfer_checked_instruction_with_extra_metas(
+            &TOKEN_PROGRAM_ID,
+            source,
+            &self.mint,
+            destination,
+            authority,
+            &[],
+            amount,
+            DECIMALS,
+            |address| {
+                let banks_client = banks_client.clone();
+                async move {
+                    banks_client
+                        .get_account(address)
+                        .await
+                        .map(|account| account.map(|account| account.data))
+                        .map_err(Into::into)
+                }
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Transfers between the ATAs of `from` and `to`, signed by `from`.
+    pub async fn transfer(
+        &mut self,
+        from: &Keypair,
+        to: &Pubkey,
+        amount: u64,
+    ) -> Result<Vec<String>, BanksClientError> {
+        let ix = self
+            .transfer_ix(
+                &self.ata(&from.pubkey()),
+                &self.ata(to),
+                &from.pubkey(),
+                amount,
+            )
+            .await;
+
+        self.send(&[ix], &[from]).await
+    }
+
+    pub fn deposit_ix(&self, user: &Pubkey, lock_duration: i64) -> Instruction {
+        self.ix(
+            crate::accounts::Deposit {
+                user: *user,
+                user_state: user_pda(user),
+                user_ata: self.ata(user),
+                vault_ata: self.ata(&vault()),
+                vault: vault(),
+                mint: self.mint,
+                instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+                system_program: SYSTEM_PROGRAM_ID,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: associated_token::ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::Deposit { lock_duration },
+        )
+    }
+
+    /// Transfers `amount` into the vault and deposits it.
+    pub async fn deposit(
+        &mut self,
+        user: &Keypair,
+        amount: u64,
+        lock_duration: i64,
+    ) -> Result<Vec<String>, BanksClientError> {
+        let transfer = self
+            .transfer_ix(
+                &self.ata(&user.pubkey()),
+                &self.ata(&vault()),
+                &user.pubkey(),
+                amount,
+            )
+            .await;
+        let deposit = self.deposit_ix(&user.pubkey(), lock_duration);
+
+        self.send(&[transfer, deposit], &[user]).await
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar().await.unwrap()
+    }
+
+    pub async fn set_clock(&mut self, unix_timestamp: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn account_data(&mut self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .map(|account| account.data)
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let data = self.account_data(address).await.unwrap();
+        T::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn token_amount(&mut self, owner: &Pubkey) -> u64 {
+        let data = self.account_data(&self.ata(owner)).await.unwrap();
+        StateWithExtensions::<TokenAccountState>::unpack(&data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    pub async fn is_frozen(&mut self, token_account: &Pubkey) -> bool {
+        let data = self.account_data(token_account).await.unwrap();
+        StateWithExtensions::<TokenAccountState>::unpack(&data)
+            .unwrap()
+            .base
+            .is_frozen()
+    }
+}
@@ -0,0 +1,471 @@
+use {
+    solana_account::{Account, AccountSharedData},
+    solana_instruction::AccountMeta,
+    solana_keypair::Keypair,
+    solana_pubkey::Pubkey,
+    solana_signer::Signer,
+    spl_tlv_account_resolution::state::ExtraAccountMetaList,
+    spl_token_2022::instruction::approve,
+    spl_transfer_hook_interface::instruction::{
+        update_extra_account_meta_list, ExecuteInstruction,
+    },
+};
+
+use super::harness::*;
+use crate::{
+    error::ErrorCode,
+    events::{PolicyViolation, TransferAllowed},
+    state::{Blackout, EnforcementMode, PolicyRules, TradingWindow},
+    InitializeExtraAccountMetaList, MintExtensions, ViolationReason,
+};
+
+#[tokio::test]
+async fn test_initialize_meta_list_through_interface() {
+    // the harness already initialized its mint's list with the interface builder
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let admin = harness.admin.pubkey();
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&sender.pubkey(), 1_000).await;
+    harness
+        .transfer(&sender, &receiver.pubkey(), 1_000)
+        .await
+        .unwrap();
+
+    // the read-only authority cannot pay, an unfunded list is refused
+    let mint = Keypair::new();
+    let create_mint = harness.create_mint_ix(&admin, &mint.pubkey(), MintExtensions::default());
+    harness.send(&[create_mint], &[&mint]).await.unwrap();
+
+    let authority = Keypair::new();
+    let [fund, initialize] = harness
+        .initialize_meta_list_ixs(&authority.pubkey(), &mint.pubkey())
+        .await;
+    assert_error(
+        harness
+            .send(std::slice::from_ref(&initialize), &[&authority])
+            .await,
+        ErrorCode::MetaListNotFunded,
+    );
+
+    // once funded any signer may initialize, the content is fixed by the program
+    harness
+        .send(&[fund.clone(), initialize.clone()], &[&authority])
+        .await
+        .unwrap();
+
+    // and only once
+    assert!(harness
+        .send(&[initialize, fund], &[&authority])
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_update_meta_list_through_interface() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let extra_account_metas = InitializeExtraAccountMetaList::extra_account_metas().unwrap();
+    let admin = harness.admin.pubkey();
+    let meta_list = harness.meta_list();
+
+    // the interface accounts with a paying authority, then the vault that names the admin
+    let update_ix = |harness: &Harness, authority: &Pubkey, extra_account_metas| {
+        let mut ix = interface_ix(update_extra_account_meta_list(
+            &address(&PROGRAM_ID),
+            &address(&harness.meta_list()),
+            &address(&harness.mint),
+            &address(authority),
+            extra_account_metas,
+        ));
+        ix.accounts[2].is_writable = true;
+        ix.accounts.extend([
+            AccountMeta::new_readonly(vault(), false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ]);
+
+        ix
+    };
+
+    let outsider = harness.new_user(*b"US").await;
+    let ix = update_ix(&harness, &outsider.pubkey(), &extra_account_metas);
+    assert_error(
+        harness.send(&[ix], &[&outsider]).await,
+        ErrorCode::Unauthorized,
+    );
+
+    // the hook reads its accounts by position, a reordered list is refused
+    let mut reordered = extra_account_metas.clone();
+    reordered.swap(0, 1);
+    let ix = update_ix(&harness, &admin, &reordered);
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        ErrorCode::InvalidExtraAccountMetas,
+    );
+
+    // lamports beyond rent exemption are refunded to the admin
+    let len = ExtraAccountMetaList::size_of(extra_account_metas.len()).unwrap();
+    let rent = harness.context.banks_client.get_rent().await.unwrap();
+    harness.airdrop(&meta_list, 1_000_000).await;
+
+    let ix = update_ix(&harness, &admin, &extra_account_metas);
+    harness.send(&[ix], &[]).await.unwrap();
+    let account = harness
+        .context
+        .banks_client
+        .get_account(meta_list)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.lamports, rent.minimum_balance(len));
+
+    let mut expected = vec![0; len];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut expected, &extra_account_metas).unwrap();
+    assert_eq!(account.data, expected);
+
+    // and a shortfall is topped up by the admin
+    harness.context.set_account(
+        &meta_list,
+        &AccountSharedData::from(Account {
+            lamports: rent.minimum_balance(len) - 1_000,
+            ..account
+        }),
+    );
+    let ix = update_ix(&harness, &admin, &[]);
+    harness.send(&[ix], &[]).await.unwrap();
+    let account = harness
+        .context
+        .banks_client
+        .get_account(meta_list)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.lamports, rent.minimum_balance(len));
+
+    // transfers keep resolving their extra accounts from the rewritten list
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&sender.pubkey(), 1_000).await;
+    harness
+        .transfer(&sender, &receiver.pubkey(), 1_000)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_delegate_for_removed_owner_is_rejected() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let owner = harness.new_user(*b"US").await;
+    let delegate = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&owner.pubkey(), 1_000).await;
+
+    let ix = approve(
+        &TOKEN_PROGRAM_ID,
+        &harness.ata(&owner.pubkey()),
+        &delegate.pubkey(),
+        &owner.pubkey(),
+        &[],
+        1_000,
+    )
+    .unwrap();
+    harness.send(&[ix], &[&owner]).await.unwrap();
+
+    let source = harness.ata(&owner.pubkey());
+    let destination = harness.ata(&receiver.pubkey());
+    let ix = harness
+        .transfer_ix(&source, &destination, &delegate.pubkey(), 100)
+        .await;
+    harness.send(&[ix], &[&delegate]).await.unwrap();
+
+    // the owner's account is left unfrozen, only the hook stands in the way
+    let ix = harness.remove_ix(&owner.pubkey(), None);
+    harness.send(&[ix], &[]).await.unwrap();
+
+    // a whitelisted delegate does not make up for the owner
+    let ix = harness
+        .transfer_ix(&source, &destination, &delegate.pubkey(), 200)
+        .await;
+    assert_error(
+        harness.send(&[ix], &[&delegate]).await,
+        ErrorCode::NotWhitelisted,
+    );
+    assert_error(
+        harness.transfer(&owner, &receiver.pubkey(), 200).await,
+        ErrorCode::NotWhitelisted,
+    );
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 100);
+}
+
+#[tokio::test]
+async fn test_vault_outflow_requires_whitelisted_delegate() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let admin = harness.admin.pubkey();
+    let user = harness.new_user(*b"US").await;
+    let treasury = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_500).await;
+    harness.deposit(&user, 1_000, 0).await.unwrap();
+    harness.transfer(&user, &vault(), 500).await.unwrap();
+    harness.set_treasury(&treasury.pubkey()).await;
+
+    // the vault is exempt as owner, the admin signing as its delegate is not
+    let outflow = harness
+        .transfer_ix(
+            &harness.ata(&vault()),
+            &harness.ata(&treasury.pubkey()),
+            &admin,
+            500,
+        )
+        .await;
+    assert_error(
+        harness
+            .send(&[harness.sweep_ix(None), outflow.clone()], &[])
+            .await,
+        ErrorCode::NotWhitelisted,
+    );
+
+    harness.whitelist(&admin, *b"US").await;
+    harness
+        .send(&[harness.sweep_ix(None), outflow], &[])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_amount(&treasury.pubkey()).await, 500);
+}
+
+#[tokio::test]
+async fn test_monitor_mode_flags_violations_without_rejecting() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"DE").await;
+    harness.mint_to(&sender.pubkey(), 1_000).await;
+
+    // no pair is allowed, so every transfer breaks the jurisdiction rule
+    let rules = PolicyRules {
+        mode: EnforcementMode::Monitor,
+        check_jurisdictions: true,
+        ..default_rules()
+    };
+    harness.set_policy(rules.clone()).await;
+
+    let logs = harness
+        .transfer(&sender, &receiver.pubkey(), 400)
+        .await
+        .unwrap();
+    let violations = events::<PolicyViolation>(&logs);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].reason,
+        ViolationReason::JurisdictionNotAllowed
+    );
+    assert_eq!(violations[0].amount, 400);
+    assert_eq!(events::<TransferAllowed>(&logs).len(), 1);
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 400);
+
+    // the same rules in enforce mode turn the flag into a rejection
+    harness
+        .set_policy(PolicyRules {
+            mode: EnforcementMode::Enforce,
+            ..rules
+        })
+        .await;
+    assert_error(
+        harness.transfer(&sender, &receiver.pubkey(), 500).await,
+        ErrorCode::JurisdictionNotAllowed,
+    );
+}
+
+#[tokio::test]
+async fn test_off_mode_allows_any_transfer() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&sender.pubkey(), 1_000).await;
+
+    // the sender's account is left unfrozen, only the hook stands in the way
+    let ix = harness.remove_ix(&sender.pubkey(), None);
+    harness.send(&[ix], &[]).await.unwrap();
+    assert_error(
+        harness.transfer(&sender, &receiver.pubkey(), 200).await,
+        ErrorCode::NotWhitelisted,
+    );
+
+    harness
+        .set_policy(PolicyRules {
+            mode: EnforcementMode::Off,
+            check_jurisdictions: true,
+            ..default_rules()
+        })
+        .await;
+    let logs = harness
+        .transfer(&sender, &receiver.pubkey(), 300)
+        .await
+        .unwrap();
+    assert!(events::<PolicyViolation>(&logs).is_empty());
+    assert_eq!(events::<TransferAllowed>(&logs).len(), 1);
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 300);
+}
+
+#[tokio::test]
+async fn test_mint_without_policy_enforces_default_rules() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let admin = harness.admin.pubkey();
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+
+    // a second mint issued by the vault, its policy is not initialized yet
+    let mint = Keypair::new();
+    let create_mint = harness.create_mint_ix(&admin, &mint.pubkey(), MintExtensions::default());
+    let [fund, initialize] = harness
+        .initialize_meta_list_ixs(&admin, &mint.pubkey())
+        .await;
+    harness
+        .send(&[create_mint, fund, initialize], &[&mint])
+        .await
+        .unwrap();
+    // whitelist removal goes through the vault's own mint
+    let remove = harness.remove_ix(&receiver.pubkey(), None);
+    harness.mint = mint.pubkey();
+    let mint_policy = harness.mint_policy();
+    assert!(harness.account_data(&mint_policy).await.is_none());
+
+    harness.mint_to(&sender.pubkey(), 1_000).await;
+    harness.mint_to(&receiver.pubkey(), 1).await;
+    harness
+        .transfer(&sender, &receiver.pubkey(), 99)
+        .await
+        .unwrap();
+
+    // the default rules check the source owner, not just the signer
+    harness.send(&[remove], &[]).await.unwrap();
+    assert_error(
+        harness.transfer(&receiver, &sender.pubkey(), 50).await,
+        ErrorCode::NotWhitelisted,
+    );
+
+    // the vault's mint authority is enough to set up a policy for a mint it does not hold
+    let ix = harness.initialize_policy_ix(PolicyRules {
+        mode: EnforcementMode::Off,
+        ..default_rules()
+    });
+    harness.send(&[ix], &[]).await.unwrap();
+    harness
+        .transfer(&receiver, &sender.pubkey(), 60)
+        .await
+        .unwrap();
+
+    harness.set_policy(default_rules()).await;
+    assert_error(
+        harness.transfer(&receiver, &sender.pubkey(), 40).await,
+        ErrorCode::NotWhitelisted,
+    );
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 40);
+}
+
+#[tokio::test]
+async fn test_max_holding_applies_to_associated_account() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&sender.pubkey(), 2_000).await;
+    harness
+        .set_policy(PolicyRules {
+            max_holding: Some(1_000),
+            ..default_rules()
+        })
+        .await;
+
+    harness
+        .transfer(&sender, &receiver.pubkey(), 600)
+        .await
+        .unwrap();
+    assert_error(
+        harness.transfer(&sender, &receiver.pubkey(), 500).await,
+        ErrorCode::MaxHoldingExceeded,
+    );
+
+    // a second account would let the receiver hold past the limit
+    let other_account = harness.create_token_account(&receiver.pubkey()).await;
+    let ix = harness
+        .transfer_ix(
+            &harness.ata(&sender.pubkey()),
+            &other_account,
+            &sender.pubkey(),
+            100,
+        )
+        .await;
+    assert_error(
+        harness.send(&[ix], &[&sender]).await,
+        ErrorCode::HoldingOutsideAssociatedAccount,
+    );
+
+    // the wallet's own limit replaces the mint-wide one
+    let ix = harness.ix(
+        crate::accounts::SetMaxHolding {
+            admin: harness.admin.pubkey(),
+            vault: vault(),
+            user: user_pda(&receiver.pubkey()),
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::SetMaxHolding {
+            user: receiver.pubkey(),
+            max_holding: Some(1_500),
+        },
+    );
+    harness.send(&[ix], &[]).await.unwrap();
+    harness
+        .transfer(&sender, &receiver.pubkey(), 900)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 1_500);
+}
+
+#[tokio::test]
+async fn test_transfers_outside_trading_window_are_rejected() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&sender.pubkey(), 1_000).await;
+
+    // Monday 2024-01-01 00:00 UTC, trading on weekdays 09:00 to 17:00 with Tuesday blacked out
+    let monday = 1_704_067_200;
+    let hour = 3_600;
+    let day = 24 * hour;
+    harness
+        .set_policy(PolicyRules {
+            trading_window: TradingWindow {
+                hours: 0xff << 9,
+                weekdays: 0b001_1111,
+                blackouts: vec![Blackout {
+                    start_ts: monday + day,
+                    end_ts: monday + 2 * day,
+                }],
+            },
+            ..default_rules()
+        })
+        .await;
+
+    harness.set_clock(monday + 8 * hour).await;
+    assert_error(
+        harness.transfer(&sender, &receiver.pubkey(), 100).await,
+        ErrorCode::TradingWindowClosed,
+    );
+
+    harness.set_clock(monday + 9 * hour).await;
+    harness
+        .transfer(&sender, &receiver.pubkey(), 100)
+        .await
+        .unwrap();
+
+    harness.set_clock(monday + day + 12 * hour).await;
+    assert_error(
+        harness.transfer(&sender, &receiver.pubkey(), 200).await,
+        ErrorCode::TradingWindowClosed,
+    );
+
+    harness.set_clock(monday + 5 * day + 12 * hour).await;
+    assert_error(
+        harness.transfer(&sender, &receiver.pubkey(), 300).await,
+        ErrorCode::TradingWindowClosed,
+    );
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 100);
+}
@@ -0,0 +1,172 @@
+use {
+    solana_instruction::Instruction,
+    solana_keypair::Keypair,
+    solana_signer::Signer,
+    spl_token_2022::{
+        extension::{BaseStateWithExtensions, StateWithExtensions},
+        state::Mint,
+    },
+    spl_token_metadata_interface::state::TokenMetadata,
+};
+
+use super::harness::*;
+use crate::{
+    error::ErrorCode, events::MetadataFieldUpdated, state::MintConfig, MetadataField,
+    MintExtensions,
+};
+
+fn update_metadata_ix(harness: &Harness, data: impl anchor_lang::InstructionData) -> Instruction {
+    harness.ix(
+        crate::accounts::UpdateTokenMetadata {
+            admin: harness.admin.pubkey(),
+            vault: vault(),
+            mint: harness.mint,
+            system_program: solana_sdk_ids::system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        data,
+    )
+}
+
+/// The mint's metadata, and whether the mint still holds no more than the rent it needs
+async fn metadata(harness: &mut Harness) -> (TokenMetadata, bool) {
+    let account = harness
+        .context
+        .banks_client
+        .get_account(harness.mint)
+        .await
+        .unwrap()
+        .unwrap();
+    let rent = harness.context.banks_client.get_rent().await.unwrap();
+    let mint = StateWithExtensions::<Mint>::unpack(&account.data).unwrap();
+
+    (
+        mint.get_variable_len_extension::<TokenMetadata>().unwrap(),
+        account.lamports == rent.minimum_balance(account.data.len()),
+    )
+}
+
+#[tokio::test]
+async fn test_update_metadata() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+
+    let ix = update_metadata_ix(
+        &harness,
+        crate::instruction::UpdateMetadataField {
+            field: MetadataField::Name,
+            value: "Vault Token Series A".to_string(),
+        },
+    );
+    let logs = harness.send(&[ix], &[]).await.unwrap();
+    assert_eq!(
+        events::<MetadataFieldUpdated>(&logs)[0].value,
+        "Vault Token Series A"
+    );
+
+    // a new key grows the mint, the admin tops up its rent
+    let ix = update_metadata_ix(
+        &harness,
+        crate::instruction::UpdateMetadataField {
+            field: MetadataField::Key("isin".to_string()),
+            value: "US0378331005".to_string(),
+        },
+    );
+    harness.send(&[ix], &[]).await.unwrap();
+
+    let (token_metadata, exact_rent) = metadata(&mut harness).await;
+    assert_eq!(token_metadata.name, "Vault Token Series A");
+    assert_eq!(
+        token_metadata.additional_metadata,
+        vec![("isin".to_string(), "US0378331005".to_string())]
+    );
+    assert!(exact_rent);
+
+    // removing it refunds the freed rent
+    let ix = update_metadata_ix(
+        &harness,
+        crate::instruction::RemoveMetadataKey {
+            key: "isin".to_string(),
+        },
+    );
+    harness.send(&[ix], &[]).await.unwrap();
+
+    let (token_metadata, exact_rent) = metadata(&mut harness).await;
+    assert!(token_metadata.additional_metadata.is_empty());
+    assert!(exact_rent);
+
+    // once renounced the metadata is frozen for good
+    let ix = update_metadata_ix(
+        &harness,
+        crate::instruction::UpdateMetadataAuthority {
+            new_authority: None,
+        },
+    );
+    harness.send(&[ix], &[]).await.unwrap();
+
+    let (token_metadata, _) = metadata(&mut harness).await;
+    assert_eq!(
+        Option::<solana_pubkey::Pubkey>::from(token_metadata.update_authority),
+        None
+    );
+
+    let ix = update_metadata_ix(
+        &harness,
+        crate::instruction::UpdateMetadataField {
+            field: MetadataField::Symbol,
+            value: "VLTA".to_string(),
+        },
+    );
+    assert!(harness.send(&[ix], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_supply_cap() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let user = harness.new_user(*b"US").await;
+
+    harness.mint_to(&user.pubkey(), MAX_SUPPLY - 1).await;
+
+    let ix = harness.mint_to_ix(&user.pubkey(), 2);
+    assert_error(harness.send(&[ix], &[]).await, ErrorCode::SupplyCapExceeded);
+
+    // the rest of the cap can still be issued
+    harness.mint_to(&user.pubkey(), 1).await;
+
+    let mint_config: MintConfig = harness.account(&harness.mint_config()).await;
+    assert_eq!(mint_config.issued, MAX_SUPPLY);
+    assert_eq!(harness.token_amount(&user.pubkey()).await, MAX_SUPPLY);
+}
+
+#[tokio::test]
+async fn test_create_mint_requires_admin() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let user = harness.new_user(*b"US").await;
+    let mint = Keypair::new();
+
+    // once the vault exists only its admin creates mints
+    let ix = harness.create_mint_ix(&user.pubkey(), &mint.pubkey(), MintExtensions::default());
+    assert_error(
+        harness.send(&[ix], &[&user, &mint]).await,
+        ErrorCode::Unauthorized,
+    );
+
+    // accounts of a secondary mint could never be thawed by whitelisting
+    let frozen = MintExtensions {
+        default_frozen: true,
+        ..Default::default()
+    };
+    let ix = harness.create_mint_ix(&harness.admin.pubkey(), &mint.pubkey(), frozen);
+    assert_error(
+        harness.send(&[ix], &[&mint]).await,
+        ErrorCode::DefaultFrozenSecondaryMint,
+    );
+
+    let ix = harness.create_mint_ix(
+        &harness.admin.pubkey(),
+        &mint.pubkey(),
+        MintExtensions::default(),
+    );
+    harness.send(&[ix], &[&mint]).await.unwrap();
+}
@@ -1,4 +1,26 @@
 #[cfg(test)]
+mod clawback;
+#[cfg(test)]
+mod harness;
+#[cfg(test)]
+mod hook;
+#[cfg(test)]
+mod mint;
+#[cfg(test)]
+mod pause;
+#[cfg(test)]
+mod transfer_fee;
+#[cfg(test)]
+mod ui_amount;
+#[cfg(test)]
+mod vault;
+#[cfg(test)]
+mod whitelist;
+#[cfg(test)]
+mod withdraw;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
 
     use {
@@ -21,6 +43,10 @@ mod tests {
 
     static PROGRAM_ID: Pubkey = crate::ID;
 
+    fn event_authority() -> Pubkey {
+        Pubkey::find_program_address(&[b"__event_authority"], &PROGRAM_ID).0
+    }
+
     // Setup function to initialize LiteSVM and create a payer keypair
     // Also loads an account from devnet into the LiteSVM environment (for testing purposes)
     fn setup() -> (LiteSVM, Keypair) {
@@ -39,7 +65,7 @@ mod tests {
 
         let program_data = std::fs::read(so_path).expect("Failed to read program SO file");
 
-        program.add_program(PROGRAM_ID, &program_data);
+        program.add_program(PROGRAM_ID, &program_data).unwrap();
 
         // Example on how to Load an account from devnet
         // LiteSVM does not have access to real Solana network data since it does not have network access,
@@ -72,6 +98,216 @@ mod tests {
         (program, payer)
     }
 
+    #[test]
+    fn test_share_accounting() {
+        let mut vault = crate::state::Vault {
+            admin: Pubkey::default(),
+            mint: Pubkey::default(),
+            vault_token_account: Pubkey::default(),
+            total_deposited: 0,
+            total_withdrawn: 0,
+            total_outstanding: 0,
+            total_shares: 0,
+            total_claimable: 0,
+            user_count: 0,
+            withdraw_cooldown: 0,
+            deposit_fee_bps: 0,
+            withdraw_fee_bps: 0,
+            treasury: Pubkey::default(),
+            accrued_fees: 0,
+            bump: 0,
+        };
+
+        // first deposit mints shares 1:1
+        assert_eq!(vault.shares_for_deposit(100), Some(100));
+        vault.total_outstanding = 100;
+        vault.total_shares = 100;
+
+        // yield raises the price per share to 1.5
+        vault.total_outstanding += 50;
+        assert_eq!(vault.assets_for_shares(100), Some(150));
+        assert_eq!(vault.shares_for_deposit(150), Some(100));
+
+        // deposits round down, withdrawals round up
+        assert_eq!(vault.shares_for_deposit(1), Some(0));
+        assert_eq!(vault.shares_for_withdraw(1), Some(1));
+        assert_eq!(vault.shares_for_withdraw(150), Some(100));
+
+        // fees round down
+        vault.deposit_fee_bps = 25;
+        assert_eq!(vault.deposit_fee(10_000), Some(25));
+        assert_eq!(vault.deposit_fee(39), Some(0));
+    }
+
+    #[test]
+    fn test_deposit_locks() {
+        let mut user = crate::state::User {
+            shares: 300,
+            locks: vec![
+                crate::state::Lock {
+                    shares: 100,
+                    unlock_ts: 1_000,
+                },
+                crate::state::Lock {
+                    shares: 50,
+                    unlock_ts: 2_000,
+                },
+            ],
+            jurisdiction: *b"US",
+            max_holding: None,
+            suspended: false,
+            bump: 0,
+        };
+
+        assert_eq!(user.unlocked_shares(500), 150);
+        assert_eq!(user.unlocked_shares(1_000), 250);
+
+        user.prune_locks(1_500);
+        assert_eq!(user.locks.len(), 1);
+        assert_eq!(user.unlocked_shares(2_000), 300);
+    }
+
+    #[test]
+    fn test_trading_window() {
+        // Monday 2024-01-01 00:00 UTC
+        let monday = 1_704_067_200;
+        let hour = 3_600;
+        let day = 24 * hour;
+
+        // weekdays 09:00 to 17:00
+        let mut window = crate::state::TradingWindow {
+            hours: 0xff << 9,
+            weekdays: 0b001_1111,
+            blackouts: vec![],
+        };
+        assert!(window.validate().is_ok());
+
+        assert!(!window.is_open(monday + 8 * hour));
+        assert!(window.is_open(monday + 9 * hour));
+        assert!(window.is_open(monday + 17 * hour - 1));
+        assert!(!window.is_open(monday + 17 * hour));
+        assert!(window.is_open(monday + 4 * day + 12 * hour));
+        assert!(!window.is_open(monday + 5 * day + 12 * hour));
+
+        // Tuesday blacked out
+        window.blackouts.push(crate::state::Blackout {
+            start_ts: monday + day,
+            end_ts: monday + 2 * day,
+        });
+        assert!(!window.is_open(monday + day + 12 * hour));
+        assert!(window.is_open(monday + 2 * day + 12 * hour));
+
+        window.hours = 1 << 24;
+        assert!(window.validate().is_err());
+    }
+
+    #[test]
+    fn test_jurisdiction_matrix() {
+        let mut rules = crate::state::PolicyRules {
+            mode: crate::state::EnforcementMode::Enforce,
+            exempt_owner: Pubkey::default(),
+            check_source_owner: true,
+            check_destination_owner: true,
+            trading_window: crate::state::TradingWindow {
+                hours: crate::state::ALL_HOURS,
+                weekdays: crate::state::ALL_WEEKDAYS,
+                blackouts: vec![],
+            },
+            check_jurisdictions: true,
+            allowed_pairs: vec![
+                crate::state::JurisdictionPair {
+                    from: *b"US",
+                    to: *b"US",
+                },
+                crate::state::JurisdictionPair {
+                    from: *b"DE",
+                    to: *b"FR",
+                },
+            ],
+            max_holding: None,
+        };
+        assert!(rules.validate().is_ok());
+
+        assert!(rules.allows_jurisdictions(*b"US", *b"US"));
+        assert!(rules.allows_jurisdictions(*b"DE", *b"FR"));
+        assert!(!rules.allows_jurisdictions(*b"FR", *b"DE"));
+        assert!(!rules.allows_jurisdictions(*b"US", *b"DE"));
+
+        rules.allowed_pairs[0].to = *b"us";
+        assert!(rules.validate().is_err());
+    }
+
+    #[test]
+    fn test_holding_limit() {
+        let mut rules = crate::state::PolicyRules {
+            mode: crate::state::EnforcementMode::Enforce,
+            exempt_owner: Pubkey::default(),
+            check_source_owner: true,
+            check_destination_owner: true,
+            trading_window: crate::state::TradingWindow {
+                hours: crate::state::ALL_HOURS,
+                weekdays: crate::state::ALL_WEEKDAYS,
+                blackouts: vec![],
+            },
+            check_jurisdictions: false,
+            allowed_pairs: vec![],
+            max_holding: None,
+        };
+        let mut user = crate::state::User {
+            shares: 0,
+            locks: vec![],
+            jurisdiction: *b"US",
+            max_holding: None,
+            suspended: false,
+            bump: 0,
+        };
+
+        assert_eq!(rules.holding_limit(Some(&user)), None);
+
+        rules.max_holding = Some(1_000);
+        assert_eq!(rules.holding_limit(None), Some(1_000));
+        assert_eq!(rules.holding_limit(Some(&user)), Some(1_000));
+
+        user.max_holding = Some(5_000);
+        assert_eq!(rules.holding_limit(Some(&user)), Some(5_000));
+    }
+
+    #[test]
+    fn test_interface_instruction_data() {
+        use spl_transfer_hook_interface::instruction::TransferHookInstruction;
+
+        let extra_account_metas =
+            crate::InitializeExtraAccountMetaList::extra_account_metas().unwrap();
+        let requested: Vec<crate::ExtraAccountMetaData> =
+            extra_account_metas.iter().map(Into::into).collect();
+
+        // standard clients must be able to build both instructions with the interface crate
+        let initialize = TransferHookInstruction::InitializeExtraAccountMetaList {
+            extra_account_metas: extra_account_metas.clone(),
+        };
+        assert_eq!(
+            initialize.pack(),
+            crate::instruction::InitializeTransferHook {
+                extra_account_metas: requested.clone(),
+            }
+            .data()
+        );
+
+        let update = TransferHookInstruction::UpdateExtraAccountMetaList {
+            extra_account_metas,
+        };
+        assert_eq!(
+            update.pack(),
+            crate::instruction::UpdateTransferHook {
+                extra_account_metas: requested.clone(),
+            }
+            .data()
+        );
+
+        assert!(crate::resolve_extra_account_metas(&requested).is_ok());
+        assert!(crate::resolve_extra_account_metas(&requested[1..]).is_err());
+    }
+
     #[test]
     fn test_deposit() {
         let (mut program, admin) = setup();
@@ -86,6 +322,9 @@ mod tests {
         let (vault_pda, _v_bump) = Pubkey::find_program_address(&[b"vault"], &PROGRAM_ID);
         let (user_state_pda, _u_bump) =
             Pubkey::find_program_address(&[b"user", user.pubkey().as_ref()], &PROGRAM_ID);
+        // the vault has no whitelist entry, the hook still resolves the PDA for it
+        let (vault_owner_user_pda, _bump) =
+            Pubkey::find_program_address(&[b"user", vault_pda.as_ref()], &PROGRAM_ID);
 
         let mint_keypair = Keypair::new();
         let mint_pubkey = mint_keypair.pubkey();
@@ -107,39 +346,73 @@ mod tests {
             &PROGRAM_ID,
         );
 
+        let (transfer_log_pda, _bump) =
+            Pubkey::find_program_address(&[b"transfer-log", mint_pubkey.as_ref()], &PROGRAM_ID);
+
+        let (mint_config_pda, _bump) =
+            Pubkey::find_program_address(&[b"mint-config", mint_pubkey.as_ref()], &PROGRAM_ID);
+
+        let (mint_policy_pda, _bump) =
+            Pubkey::find_program_address(&[b"mint-policy", mint_pubkey.as_ref()], &PROGRAM_ID);
+
         let setup_ixs = vec![
-            // Init Mint and Send to user
+            // Init Mint
             Instruction {
                 program_id: PROGRAM_ID,
                 accounts: crate::accounts::TokenFactory {
                     admin: admin_pubkey,
-                    user: user.pubkey(),
-                    mint: mint_keypair.pubkey(),
-                    user_ata: user_ata,
+                    vault: vault_pda,
+                    mint: mint_pubkey,
+                    mint_config: mint_config_pda,
+                    transfer_log: transfer_log_pda,
                     system_program: solana_sdk_ids::system_program::ID,
                     token_program: anchor_spl::token_2022::ID,
-                    associated_token_program: associated_token::ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
                 }
                 .to_account_metas(None),
-                data: crate::instruction::MintToken {
-                    amount: 1_000_000_000_000,
+                data: crate::instruction::CreateMint {
+                    decimals: 9,
+                    max_supply: 1_000_000_000_000,
                     name: "test token".to_string(),
                     symbol: "TEST".to_string(),
                     uri: "".to_string(),
+                    extensions: crate::MintExtensions {
+                        default_frozen: true,
+                        pausable: true,
+                        transfer_fee: None,
+                        ui_amount: None,
+                    },
                 }
                 .data(),
             },
+            // Fund the extra account meta list, the interface's authority cannot pay for it
+            solana_system_interface::instruction::transfer(
+                &admin_pubkey,
+                &meta_list_pda,
+                program.minimum_balance_for_rent_exemption(
+                    spl_tlv_account_resolution::state::ExtraAccountMetaList::size_of(
+                        crate::InitializeExtraAccountMetaList::extra_account_metas()
+                            .unwrap()
+                            .len(),
+                    )
+                    .unwrap(),
+                ),
+            ),
             // Init extra account meta list
             Instruction {
                 program_id: PROGRAM_ID,
                 accounts: crate::accounts::InitializeExtraAccountMetaList {
-                    payer: admin_pubkey,
                     extra_account_meta_list: meta_list_pda,
                     mint: mint_pubkey,
+                    authority: admin_pubkey,
                     system_program: solana_sdk_ids::system_program::ID,
                 }
                 .to_account_metas(None),
-                data: crate::instruction::InitializeTransferHook {}.data(),
+                data: crate::instruction::InitializeTransferHook {
+                    extra_account_metas: vec![],
+                }
+                .data(),
             },
             // Initialize Vault
             Instruction {
@@ -152,10 +425,43 @@ mod tests {
                     associated_token_program: associated_token::ID,
                     token_program: anchor_spl::token_2022::ID,
                     system_program: SYSTEM_PROGRAM_ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
                 }
                 .to_account_metas(None),
                 data: crate::instruction::InitializeVault {}.data(),
             },
+            // Initialize Mint Policy
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::InitializeMintPolicy {
+                    admin: admin_pubkey,
+                    vault: vault_pda,
+                    mint: mint_pubkey,
+                    mint_policy: mint_policy_pda,
+                    system_program: SYSTEM_PROGRAM_ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::InitializeMintPolicy {
+                    rules: crate::state::PolicyRules {
+                        mode: crate::state::EnforcementMode::Enforce,
+                        exempt_owner: vault_pda,
+                        check_source_owner: true,
+                        check_destination_owner: true,
+                        trading_window: crate::state::TradingWindow {
+                            hours: crate::state::ALL_HOURS,
+                            weekdays: crate::state::ALL_WEEKDAYS,
+                            blackouts: vec![],
+                        },
+                        check_jurisdictions: false,
+                        allowed_pairs: vec![],
+                        max_holding: None,
+                    },
+                }
+                .data(),
+            },
             // Add User to Whitelist
             Instruction {
                 program_id: PROGRAM_ID,
@@ -163,11 +469,43 @@ mod tests {
                     admin: admin_pubkey,
                     vault: vault_pda,
                     user: user_state_pda,
+                    wallet: user.pubkey(),
+                    mint: mint_pubkey,
+                    user_token_account: user_ata,
                     system_program: SYSTEM_PROGRAM_ID,
+                    token_program: anchor_spl::token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
                 }
                 .to_account_metas(None),
                 data: crate::instruction::AddToWhitelist {
                     user: user.pubkey(),
+                    jurisdiction: *b"US",
+                }
+                .data(),
+            },
+            // Mint to whitelisted user
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::MintToWhitelisted {
+                    admin: admin_pubkey,
+                    vault: vault_pda,
+                    recipient: user.pubkey(),
+                    recipient_state: user_state_pda,
+                    mint: mint_pubkey,
+                    mint_config: mint_config_pda,
+                    mint_policy: mint_policy_pda,
+                    recipient_ata: user_ata,
+                    system_program: SYSTEM_PROGRAM_ID,
+                    token_program: anchor_spl::token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::MintToWhitelisted {
+                    amount: 1_000_000_000_000, // 1000 tokens
                 }
                 .data(),
             },
@@ -196,8 +534,13 @@ mod tests {
 
         transfer_ix.accounts.extend(vec![
             AccountMeta::new_readonly(meta_list_pda, false),
-            AccountMeta::new_readonly(vault_pda, false),
             AccountMeta::new_readonly(user_state_pda, false),
+            AccountMeta::new_readonly(user_state_pda, false),
+            AccountMeta::new_readonly(vault_owner_user_pda, false),
+            AccountMeta::new_readonly(mint_policy_pda, false),
+            AccountMeta::new(transfer_log_pda, false),
+            AccountMeta::new_readonly(event_authority(), false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
             AccountMeta::new_readonly(PROGRAM_ID, false),
         ]);
 
@@ -214,9 +557,11 @@ mod tests {
                 system_program: SYSTEM_PROGRAM_ID,
                 token_program: anchor_spl::token_2022::ID,
                 associated_token_program: associated_token::ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
             }
             .to_account_metas(None),
-            data: crate::instruction::Deposit {}.data(),
+            data: crate::instruction::Deposit { lock_duration: 0 }.data(),
         };
 
         let message = Message::new(&[transfer_ix, deposit_ix], Some(&user.pubkey()));
@@ -233,10 +578,25 @@ mod tests {
             crate::state::User::try_deserialize(&mut user_state_account.data.as_ref()).unwrap();
 
         assert_eq!(
-            user_state.balance, 10_000_000_000,
-            "User state balance should match deposit"
+            user_state.shares, 10_000_000_000,
+            "First deposit should mint shares 1:1"
         );
 
+        let vault_account = program.get_account(&vault_pda).unwrap();
+        let vault = crate::state::Vault::try_deserialize(&mut vault_account.data.as_ref()).unwrap();
+
+        assert_eq!(vault.total_deposited, 10_000_000_000);
+        assert_eq!(vault.total_outstanding, 10_000_000_000);
+        assert_eq!(vault.user_count, 1);
+
+        let mint_config_account = program.get_account(&mint_config_pda).unwrap();
+        let mint_config =
+            crate::state::MintConfig::try_deserialize(&mut mint_config_account.data.as_ref())
+                .unwrap();
+
+        assert_eq!(mint_config.issued, 1_000_000_000_000);
+        assert_eq!(mint_config.max_supply, 1_000_000_000_000);
+
         let vault_ata_account = program.get_account(&vault_ata).unwrap();
 
         // get amount manually, unpacking throws error
@@ -246,6 +606,15 @@ mod tests {
             vault_amount, 10_000_000_000,
             "Vault ATA should have received tokens"
         );
+
+        let transfer_log_account = program.get_account(&transfer_log_pda).unwrap();
+        let transfer_log: crate::state::TransferLog =
+            bytemuck::pod_read_unaligned(&transfer_log_account.data[8..]);
+
+        assert_eq!(transfer_log.total, 1, "Transfer should be recorded");
+        assert_eq!(transfer_log.records[0].source_owner, user.pubkey());
+        assert_eq!(transfer_log.records[0].destination_owner, vault_pda);
+        assert_eq!(transfer_log.records[0].amount, 10_000_000_000);
     }
 
     #[test]
@@ -262,6 +631,9 @@ mod tests {
         let (vault_pda, _v_bump) = Pubkey::find_program_address(&[b"vault"], &PROGRAM_ID);
         let (user_state_pda, _u_bump) =
             Pubkey::find_program_address(&[b"user", user.pubkey().as_ref()], &PROGRAM_ID);
+        // the vault has no whitelist entry, the hook still resolves the PDA for it
+        let (vault_owner_user_pda, _bump) =
+            Pubkey::find_program_address(&[b"user", vault_pda.as_ref()], &PROGRAM_ID);
 
         let mint_keypair = Keypair::new();
         let mint_pubkey = mint_keypair.pubkey();
@@ -283,39 +655,73 @@ mod tests {
             &PROGRAM_ID,
         );
 
+        let (transfer_log_pda, _bump) =
+            Pubkey::find_program_address(&[b"transfer-log", mint_pubkey.as_ref()], &PROGRAM_ID);
+
+        let (mint_config_pda, _bump) =
+            Pubkey::find_program_address(&[b"mint-config", mint_pubkey.as_ref()], &PROGRAM_ID);
+
+        let (mint_policy_pda, _bump) =
+            Pubkey::find_program_address(&[b"mint-policy", mint_pubkey.as_ref()], &PROGRAM_ID);
+
         let setup_ixs = vec![
-            // Init Mint and Send to user
+            // Init Mint
             Instruction {
                 program_id: PROGRAM_ID,
                 accounts: crate::accounts::TokenFactory {
                     admin: admin_pubkey,
-                    user: user.pubkey(),
-                    mint: mint_keypair.pubkey(),
-                    user_ata: user_ata,
+                    vault: vault_pda,
+                    mint: mint_pubkey,
+                    mint_config: mint_config_pda,
+                    transfer_log: transfer_log_pda,
                     system_program: solana_sdk_ids::system_program::ID,
                     token_program: anchor_spl::token_2022::ID,
-                    associated_token_program: associated_token::ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
                 }
                 .to_account_metas(None),
-                data: crate::instruction::MintToken {
-                    amount: 1_000_000_000_000, // 1000 tokens
+                data: crate::instruction::CreateMint {
+                    decimals: 9,
+                    max_supply: 1_000_000_000_000,
                     name: "test token".to_string(),
                     symbol: "TEST".to_string(),
                     uri: "".to_string(),
+                    extensions: crate::MintExtensions {
+                        default_frozen: true,
+                        pausable: true,
+                        transfer_fee: None,
+                        ui_amount: None,
+                    },
                 }
                 .data(),
             },
+            // Fund the extra account meta list, the interface's authority cannot pay for it
+            solana_system_interface::instruction::transfer(
+                &admin_pubkey,
+                &meta_list_pda,
+                program.minimum_balance_for_rent_exemption(
+                    spl_tlv_account_resolution::state::ExtraAccountMetaList::size_of(
+                        crate::InitializeExtraAccountMetaList::extra_account_metas()
+                            .unwrap()
+                            .len(),
+                    )
+                    .unwrap(),
+                ),
+            ),
             // Init extra account meta list
             Instruction {
                 program_id: PROGRAM_ID,
                 accounts: crate::accounts::InitializeExtraAccountMetaList {
-                    payer: admin_pubkey,
                     extra_account_meta_list: meta_list_pda,
                     mint: mint_pubkey,
+                    authority: admin_pubkey,
                     system_program: solana_sdk_ids::system_program::ID,
                 }
                 .to_account_metas(None),
-                data: crate::instruction::InitializeTransferHook {}.data(),
+                data: crate::instruction::InitializeTransferHook {
+                    extra_account_metas: vec![],
+                }
+                .data(),
             },
             // Initialize Vault
             Instruction {
@@ -328,10 +734,43 @@ mod tests {
                     associated_token_program: associated_token::ID,
                     token_program: anchor_spl::token_2022::ID,
                     system_program: SYSTEM_PROGRAM_ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
                 }
                 .to_account_metas(None),
                 data: crate::instruction::InitializeVault {}.data(),
             },
+            // Initialize Mint Policy
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::InitializeMintPolicy {
+                    admin: admin_pubkey,
+                    vault: vault_pda,
+                    mint: mint_pubkey,
+                    mint_policy: mint_policy_pda,
+                    system_program: SYSTEM_PROGRAM_ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::InitializeMintPolicy {
+                    rules: crate::state::PolicyRules {
+                        mode: crate::state::EnforcementMode::Enforce,
+                        exempt_owner: vault_pda,
+                        check_source_owner: true,
+                        check_destination_owner: true,
+                        trading_window: crate::state::TradingWindow {
+                            hours: crate::state::ALL_HOURS,
+                            weekdays: crate::state::ALL_WEEKDAYS,
+                            blackouts: vec![],
+                        },
+                        check_jurisdictions: false,
+                        allowed_pairs: vec![],
+                        max_holding: None,
+                    },
+                }
+                .data(),
+            },
             // Add User to Whitelist
             Instruction {
                 program_id: PROGRAM_ID,
@@ -339,11 +778,43 @@ mod tests {
                     admin: admin_pubkey,
                     vault: vault_pda,
                     user: user_state_pda,
+                    wallet: user.pubkey(),
+                    mint: mint_pubkey,
+                    user_token_account: user_ata,
                     system_program: SYSTEM_PROGRAM_ID,
+                    token_program: anchor_spl::token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
                 }
                 .to_account_metas(None),
                 data: crate::instruction::AddToWhitelist {
                     user: user.pubkey(),
+                    jurisdiction: *b"US",
+                }
+                .data(),
+            },
+            // Mint to whitelisted user
+            Instruction {
+                program_id: PROGRAM_ID,
+                accounts: crate::accounts::MintToWhitelisted {
+                    admin: admin_pubkey,
+                    vault: vault_pda,
+                    recipient: user.pubkey(),
+                    recipient_state: user_state_pda,
+                    mint: mint_pubkey,
+                    mint_config: mint_config_pda,
+                    mint_policy: mint_policy_pda,
+                    recipient_ata: user_ata,
+                    system_program: SYSTEM_PROGRAM_ID,
+                    token_program: anchor_spl::token_2022::ID,
+                    associated_token_program: associated_token::ID,
+                    event_authority: event_authority(),
+                    program: PROGRAM_ID,
+                }
+                .to_account_metas(None),
+                data: crate::instruction::MintToWhitelisted {
+                    amount: 1_000_000_000_000, // 1000 tokens
                 }
                 .data(),
             },
@@ -372,8 +843,13 @@ mod tests {
 
         transfer_ix.accounts.extend(vec![
             AccountMeta::new_readonly(meta_list_pda, false),
-            AccountMeta::new_readonly(vault_pda, false),
             AccountMeta::new_readonly(user_state_pda, false),
+            AccountMeta::new_readonly(user_state_pda, false),
+            AccountMeta::new_readonly(vault_owner_user_pda, false),
+            AccountMeta::new_readonly(mint_policy_pda, false),
+            AccountMeta::new(transfer_log_pda, false),
+            AccountMeta::new_readonly(event_authority(), false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
             AccountMeta::new_readonly(PROGRAM_ID, false),
         ]);
 
@@ -390,9 +866,11 @@ mod tests {
                 system_program: SYSTEM_PROGRAM_ID,
                 token_program: anchor_spl::token_2022::ID,
                 associated_token_program: associated_token::ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
             }
             .to_account_metas(None),
-            data: crate::instruction::Deposit {}.data(),
+            data: crate::instruction::Deposit { lock_duration: 0 }.data(),
         };
 
         let message = Message::new(&[transfer_ix, deposit_ix], Some(&user.pubkey()));
@@ -403,23 +881,58 @@ mod tests {
             .send_transaction(tx)
             .expect("Introspection check failed");
 
-        let withdraw_ix = Instruction {
+        let (withdraw_request_pda, _bump) = Pubkey::find_program_address(
+            &[
+                b"withdraw-request",
+                user.pubkey().as_ref(),
+                &0u64.to_le_bytes(),
+            ],
+            &PROGRAM_ID,
+        );
+
+        let request_withdraw_ix = Instruction {
             program_id: PROGRAM_ID,
-            accounts: crate::accounts::Withdraw {
+            accounts: crate::accounts::RequestWithdraw {
                 user: user.pubkey(),
                 vault: vault_pda,
                 user_account: user_state_pda,
-                vault_token_account: vault_ata,
-                token_program: token_2022::ID,
-                instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+                withdraw_request: withdraw_request_pda,
+                system_program: SYSTEM_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
             }
             .to_account_metas(None),
-            data: crate::instruction::Withdraw {
+            data: crate::instruction::RequestWithdraw {
                 amount: 10_000_000_000,
+                index: 0,
             }
             .data(),
         };
 
+        program
+            .send_transaction(Transaction::new(
+                &[&user],
+                Message::new(&[request_withdraw_ix], Some(&user.pubkey())),
+                program.latest_blockhash(),
+            ))
+            .expect("Failed to request withdraw");
+
+        let claim_withdraw_ix = Instruction {
+            program_id: PROGRAM_ID,
+            accounts: crate::accounts::ClaimWithdraw {
+                user: user.pubkey(),
+                vault: vault_pda,
+                withdraw_request: withdraw_request_pda,
+                vault_token_account: vault_ata,
+                token_program: token_2022::ID,
+                instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            }
+            .to_account_metas(None),
+            data: crate::instruction::ClaimWithdraw { index: 0 }.data(),
+        };
+
         let mut transfer_out_ix = spl_token_2022::instruction::transfer_checked(
             &token_2022::ID,
             &vault_ata,
@@ -434,12 +947,18 @@ mod tests {
 
         transfer_out_ix.accounts.extend(vec![
             AccountMeta::new_readonly(meta_list_pda, false),
-            AccountMeta::new_readonly(vault_pda, false),
             AccountMeta::new_readonly(user_state_pda, false),
+            AccountMeta::new_readonly(vault_owner_user_pda, false),
+            AccountMeta::new_readonly(user_state_pda, false),
+            AccountMeta::new_readonly(mint_policy_pda, false),
+            AccountMeta::new(transfer_log_pda, false),
+            AccountMeta::new_readonly(event_authority(), false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
             AccountMeta::new_readonly(PROGRAM_ID, false),
         ]);
 
-        let withdraw_msg = Message::new(&[withdraw_ix, transfer_out_ix], Some(&user.pubkey()));
+        let withdraw_msg =
+            Message::new(&[claim_withdraw_ix, transfer_out_ix], Some(&user.pubkey()));
 
         program
             .send_transaction(Transaction::new(
@@ -453,6 +972,12 @@ mod tests {
         let user_state =
             crate::state::User::try_deserialize(&mut user_state_acc.data.as_ref()).unwrap();
 
-        assert_eq!(user_state.balance, 0);
+        assert_eq!(user_state.shares, 0);
+
+        let withdraw_request_acc = program.get_account(&withdraw_request_pda);
+        assert!(
+            withdraw_request_acc.is_none_or(|account| account.lamports == 0),
+            "Withdraw request should be closed after claiming"
+        );
     }
 }
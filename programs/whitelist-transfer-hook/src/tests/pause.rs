@@ -0,0 +1,96 @@
+use {solana_keypair::Keypair, solana_signer::Signer};
+
+use super::harness::*;
+use crate::{events::Deposited, state::Vault, MintExtensions, TransferFeeArgs};
+
+#[tokio::test]
+async fn test_pause_halts_transfers_and_mints() {
+    let mut harness = Harness::new(MintExtensions {
+        pausable: true,
+        ..Default::default()
+    })
+    .await;
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&sender.pubkey(), 1_000).await;
+
+    let ix = harness.pause_ix(crate::instruction::PauseMint {});
+    harness.send(&[ix], &[]).await.unwrap();
+
+    let paused = spl_token_2022::error::TokenError::MintPaused as u32;
+    assert_error(
+        harness.transfer(&sender, &receiver.pubkey(), 100).await,
+        paused,
+    );
+    let ix = harness.mint_to_ix(&receiver.pubkey(), 100);
+    assert_error(harness.send(&[ix], &[]).await, paused);
+
+    let ix = harness.pause_ix(crate::instruction::ResumeMint {});
+    harness.send(&[ix], &[]).await.unwrap();
+
+    harness
+        .transfer(&sender, &receiver.pubkey(), 100)
+        .await
+        .unwrap();
+    harness.mint_to(&receiver.pubkey(), 100).await;
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 200);
+}
+
+#[tokio::test]
+async fn test_pause_secondary_mint() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let receiver = harness.new_user(*b"US").await;
+
+    let mint = Keypair::new();
+    let ix = harness.create_mint_ix(
+        &harness.admin.pubkey(),
+        &mint.pubkey(),
+        MintExtensions {
+            pausable: true,
+            ..Default::default()
+        },
+    );
+    harness.send(&[ix], &[&mint]).await.unwrap();
+    harness.mint = mint.pubkey();
+
+    let ix = harness.pause_ix(crate::instruction::PauseMint {});
+    harness.send(&[ix], &[]).await.unwrap();
+
+    let ix = harness.mint_to_ix(&receiver.pubkey(), 100);
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        spl_token_2022::error::TokenError::MintPaused as u32,
+    );
+
+    let ix = harness.pause_ix(crate::instruction::ResumeMint {});
+    harness.send(&[ix], &[]).await.unwrap();
+
+    harness.mint_to(&receiver.pubkey(), 100).await;
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 100);
+}
+
+#[tokio::test]
+async fn test_deposit_into_pausable_transfer_fee_mint() {
+    // the mint carries extensions only the newer Token-2022 knows, the fee must still be read
+    let mut harness = Harness::new(MintExtensions {
+        pausable: true,
+        transfer_fee: Some(TransferFeeArgs {
+            basis_points: 100,
+            maximum_fee: 1_000_000,
+        }),
+        ..Default::default()
+    })
+    .await;
+    let user = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_000_000).await;
+
+    let logs = harness.deposit(&user, 1_000_000, 0).await.unwrap();
+
+    let deposited = events::<Deposited>(&logs);
+    assert_eq!(deposited[0].transfer_fee, 10_000);
+    assert_eq!(deposited[0].shares_minted, 990_000);
+
+    let vault_state: Vault = harness.account(&vault()).await;
+    assert_eq!(vault_state.total_deposited, 990_000);
+    assert_eq!(harness.token_amount(&vault()).await, 990_000);
+}
@@ -0,0 +1,177 @@
+use {
+    solana_instruction::{AccountMeta, Instruction},
+    solana_keypair::Keypair,
+    solana_pubkey::Pubkey,
+    solana_signer::Signer,
+};
+
+use super::harness::*;
+use crate::{
+    error::ErrorCode,
+    events::{TransferFeesHarvested, TransferFeesWithdrawn},
+    MintExtensions, TransferFeeArgs,
+};
+
+#[tokio::test]
+async fn test_harvest_and_withdraw_transfer_fees() {
+    let mut harness = Harness::new(MintExtensions {
+        transfer_fee: Some(TransferFeeArgs {
+            basis_points: 100,
+            maximum_fee: 1_000_000,
+        }),
+        ..Default::default()
+    })
+    .await;
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    let treasury = harness.new_user(*b"US").await;
+    harness.mint_to(&sender.pubkey(), 100_000).await;
+
+    // the fee is withheld in the destination account
+    harness
+        .transfer(&sender, &receiver.pubkey(), 100_000)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_amount(&receiver.pubkey()).await, 99_000);
+
+    harness.set_treasury(&treasury.pubkey()).await;
+    let withdraw_ix = harness.ix(
+        crate::accounts::WithdrawTransferFees {
+            admin: harness.admin.pubkey(),
+            vault: vault(),
+            mint: harness.mint,
+            treasury: harness.ata(&treasury.pubkey()),
+            destination: harness.ata(&treasury.pubkey()),
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::WithdrawTransferFees {},
+    );
+
+    // nothing reached the mint before the harvest
+    assert_error(
+        harness.send(std::slice::from_ref(&withdraw_ix), &[]).await,
+        ErrorCode::NoFees,
+    );
+
+    let mut harvest_ix = harness.ix(
+        crate::accounts::HarvestTransferFees {
+            mint: harness.mint,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::HarvestTransferFees {},
+    );
+    harvest_ix
+        .accounts
+        .push(AccountMeta::new(harness.ata(&receiver.pubkey()), false));
+    let logs = harness.send(&[harvest_ix, withdraw_ix], &[]).await.unwrap();
+    assert_eq!(events::<TransferFeesHarvested>(&logs)[0].accounts, 1);
+
+    let withdrawn = events::<TransferFeesWithdrawn>(&logs);
+    assert_eq!(withdrawn[0].amount, 1_000);
+    assert_eq!(withdrawn[0].treasury, harness.ata(&treasury.pubkey()));
+    assert_eq!(harness.token_amount(&treasury.pubkey()).await, 1_000);
+}
+
+#[tokio::test]
+async fn test_withdraw_transfer_fees_of_secondary_mint() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let admin = harness.admin.pubkey();
+    let sender = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    let treasury = harness.new_user(*b"US").await;
+    harness.set_treasury(&treasury.pubkey()).await;
+    let treasury_account = harness.ata(&treasury.pubkey());
+
+    let mint = Keypair::new();
+    let create_mint = harness.create_mint_ix(
+        &admin,
+        &mint.pubkey(),
+        MintExtensions {
+            transfer_fee: Some(TransferFeeArgs {
+                basis_points: 100,
+                maximum_fee: 1_000_000,
+            }),
+            ..Default::default()
+        },
+    );
+    let [fund, initialize] = harness
+        .initialize_meta_list_ixs(&admin, &mint.pubkey())
+        .await;
+    harness
+        .send(&[create_mint, fund, initialize], &[&mint])
+        .await
+        .unwrap();
+    harness.mint = mint.pubkey();
+
+    harness.mint_to(&sender.pubkey(), 100_000).await;
+    harness.mint_to(&receiver.pubkey(), 1).await;
+    harness.mint_to(&treasury.pubkey(), 1).await;
+    harness
+        .transfer(&sender, &receiver.pubkey(), 100_000)
+        .await
+        .unwrap();
+
+    let mut harvest_ix = harness.ix(
+        crate::accounts::HarvestTransferFees {
+            mint: harness.mint,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::HarvestTransferFees {},
+    );
+    harvest_ix
+        .accounts
+        .push(AccountMeta::new(harness.ata(&receiver.pubkey()), false));
+    harness.send(&[harvest_ix], &[]).await.unwrap();
+
+    let withdraw_ix = |harness: &Harness, destination: Pubkey| -> Instruction {
+        harness.ix(
+            crate::accounts::WithdrawTransferFees {
+                admin: harness.admin.pubkey(),
+                vault: vault(),
+                mint: harness.mint,
+                treasury: treasury_account,
+                destination,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::WithdrawTransferFees {},
+        )
+    };
+
+    // the fees go to the treasury's owner and to no one else
+    let ix = withdraw_ix(&harness, harness.ata(&receiver.pubkey()));
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        anchor_lang::error::ErrorCode::ConstraintTokenOwner,
+    );
+
+    let destination = harness.ata(&treasury.pubkey());
+    let logs = harness
+        .send(&[withdraw_ix(&harness, destination)], &[])
+        .await
+        .unwrap();
+
+    let withdrawn = events::<TransferFeesWithdrawn>(&logs);
+    assert_eq!(withdrawn[0].mint, mint.pubkey());
+    assert_eq!(withdrawn[0].treasury, destination);
+    assert_eq!(harness.token_amount(&treasury.pubkey()).await, 1_001);
+}
+
+#[tokio::test]
+async fn test_deposit_requires_preceding_transfer() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let user = harness.new_user(*b"US").await;
+
+    let ix = harness.deposit_ix(&user.pubkey(), 0);
+    assert_error(
+        harness.send(&[ix], &[&user]).await,
+        ErrorCode::MissingTransferInstruction,
+    );
+}
@@ -0,0 +1,159 @@
+use {
+    solana_instruction::Instruction,
+    solana_keypair::Keypair,
+    solana_signer::Signer,
+    spl_token_2022::{
+        extension::{
+            interest_bearing_mint::InterestBearingConfig, scaled_ui_amount::ScaledUiAmountConfig,
+            BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Mint,
+    },
+};
+
+use super::harness::*;
+use crate::{
+    error::ErrorCode,
+    events::{InterestRateUpdated, UiMultiplierUpdated},
+    MintExtensions, UiAmountConfig,
+};
+
+fn update_ix(harness: &Harness, data: impl anchor_lang::InstructionData) -> Instruction {
+    harness.ix(
+        crate::accounts::UpdateUiAmount {
+            admin: harness.admin.pubkey(),
+            vault: vault(),
+            mint: harness.mint,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        data,
+    )
+}
+
+#[tokio::test]
+async fn test_update_interest_rate() {
+    let mut harness = Harness::new(MintExtensions {
+        ui_amount: Some(UiAmountConfig::InterestBearing { rate: 500 }),
+        ..Default::default()
+    })
+    .await;
+    let user = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_000).await;
+
+    let ix = update_ix(
+        &harness,
+        crate::instruction::UpdateInterestRate { rate: 250 },
+    );
+    let logs = harness.send(&[ix], &[]).await.unwrap();
+    assert_eq!(events::<InterestRateUpdated>(&logs)[0].rate, 250);
+
+    let mint = harness.mint;
+    let data = harness.account_data(&mint).await.unwrap();
+    let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap();
+    let config = mint.get_extension::<InterestBearingConfig>().unwrap();
+    assert_eq!(i16::from(config.current_rate), 250);
+
+    // only the displayed amount accrues, the raw balance stays put
+    assert_eq!(harness.token_amount(&user.pubkey()).await, 1_000);
+}
+
+#[tokio::test]
+async fn test_update_interest_rate_of_secondary_mint() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+
+    let mint = Keypair::new();
+    let ix = harness.create_mint_ix(
+        &harness.admin.pubkey(),
+        &mint.pubkey(),
+        MintExtensions {
+            ui_amount: Some(UiAmountConfig::InterestBearing { rate: 500 }),
+            ..Default::default()
+        },
+    );
+    harness.send(&[ix], &[&mint]).await.unwrap();
+    harness.mint = mint.pubkey();
+
+    let ix = update_ix(
+        &harness,
+        crate::instruction::UpdateInterestRate { rate: -100 },
+    );
+    harness.send(&[ix], &[]).await.unwrap();
+
+    let data = harness.account_data(&mint.pubkey()).await.unwrap();
+    let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap();
+    let config = mint.get_extension::<InterestBearingConfig>().unwrap();
+    assert_eq!(i16::from(config.current_rate), -100);
+}
+
+#[tokio::test]
+async fn test_update_ui_multiplier() {
+    let mut harness = Harness::new(MintExtensions {
+        ui_amount: Some(UiAmountConfig::ScaledUiAmount { multiplier: 1.0 }),
+        ..Default::default()
+    })
+    .await;
+    let user = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_000).await;
+
+    let ix = update_ix(
+        &harness,
+        crate::instruction::UpdateUiMultiplier {
+            multiplier: 0.0,
+            effective_timestamp: 0,
+        },
+    );
+    assert_error(harness.send(&[ix], &[]).await, ErrorCode::InvalidMultiplier);
+
+    // a 2:1 split scheduled for later
+    let effective_timestamp = harness.clock().await.unix_timestamp + 86_400;
+    let ix = update_ix(
+        &harness,
+        crate::instruction::UpdateUiMultiplier {
+            multiplier: 2.0,
+            effective_timestamp,
+        },
+    );
+    let logs = harness.send(&[ix], &[]).await.unwrap();
+    let updated = events::<UiMultiplierUpdated>(&logs);
+    assert_eq!(updated[0].multiplier, 2.0);
+    assert_eq!(updated[0].effective_timestamp, effective_timestamp);
+
+    let mint = harness.mint;
+    let data = harness.account_data(&mint).await.unwrap();
+    let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap();
+    let config = mint.get_extension::<ScaledUiAmountConfig>().unwrap();
+    assert_eq!(f64::from(config.multiplier), 1.0);
+    assert_eq!(f64::from(config.new_multiplier), 2.0);
+    assert_eq!(
+        i64::from(config.new_multiplier_effective_timestamp),
+        effective_timestamp
+    );
+    assert_eq!(harness.token_amount(&user.pubkey()).await, 1_000);
+}
+
+#[tokio::test]
+async fn test_distribute_yield_requires_preceding_transfer() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let user = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_000_000).await;
+    harness.deposit(&user, 1_000_000, 0).await.unwrap();
+
+    let ix = harness.ix(
+        crate::accounts::DistributeYield {
+            admin: harness.admin.pubkey(),
+            vault: vault(),
+            mint: harness.mint,
+            vault_token_account: harness.ata(&vault()),
+            instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::DistributeYield {},
+    );
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        ErrorCode::MissingTransferInstruction,
+    );
+}
@@ -0,0 +1,232 @@
+use {anchor_lang::InstructionData, solana_keypair::Keypair, solana_signer::Signer};
+
+use super::harness::*;
+use crate::{
+    error::ErrorCode,
+    events::{FeesCollected, SurplusSwept, VaultReconciled},
+    state::{User, Vault},
+    MintExtensions,
+};
+
+/// A depositor holding 1_000_000 shares and 500_000 tokens sent to the vault outside `deposit`
+async fn setup() -> (Harness, Keypair) {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+
+    let user = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_500_000).await;
+    harness.deposit(&user, 1_000_000, 0).await.unwrap();
+    harness.transfer(&user, &vault(), 500_000).await.unwrap();
+
+    (harness, user)
+}
+
+#[tokio::test]
+async fn test_sweep_surplus_to_beneficiary() {
+    let (mut harness, user) = setup().await;
+    let wallet = user.pubkey();
+
+    // the entry of one wallet cannot be credited under the name of another
+    let mut ix = harness.sweep_ix(Some(&wallet));
+    ix.data = crate::instruction::SweepSurplus {
+        beneficiary: Some(harness.admin.pubkey()),
+    }
+    .data();
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        ErrorCode::InvalidBeneficiary,
+    );
+
+    let logs = harness
+        .send(&[harness.sweep_ix(Some(&wallet))], &[])
+        .await
+        .unwrap();
+
+    let swept = events::<SurplusSwept>(&logs);
+    assert_eq!(swept[0].amount, 500_000);
+    assert_eq!(swept[0].beneficiary, Some(wallet));
+    assert_eq!(swept[0].shares, 500_000);
+
+    let user_state: User = harness.account(&user_pda(&wallet)).await;
+    assert_eq!(user_state.shares, 1_500_000);
+
+    // the surplus is now owed to the beneficiary, the vault holds no more than its liabilities
+    let vault_state: Vault = harness.account(&vault()).await;
+    assert_eq!(
+        vault_state.liabilities(),
+        Some(harness.token_amount(&vault()).await)
+    );
+}
+
+#[tokio::test]
+async fn test_sweep_surplus_to_treasury() {
+    let (mut harness, _user) = setup().await;
+    let admin = harness.admin.pubkey();
+
+    // the admin moves the surplus as a delegate, the hook requires it to be whitelisted
+    harness.whitelist(&admin, *b"US").await;
+    harness.set_treasury(&admin).await;
+
+    // taking more than the surplus would dip into the depositors' tokens
+    let transfer = harness
+        .transfer_ix(
+            &harness.ata(&vault()),
+            &harness.ata(&admin),
+            &admin,
+            500_001,
+        )
+        .await;
+    assert_error(
+        harness.send(&[harness.sweep_ix(None), transfer], &[]).await,
+        anchor_lang::error::ErrorCode::RequireEqViolated,
+    );
+
+    let transfer = harness
+        .transfer_ix(
+            &harness.ata(&vault()),
+            &harness.ata(&admin),
+            &admin,
+            500_000,
+        )
+        .await;
+    let logs = harness
+        .send(&[harness.sweep_ix(None), transfer], &[])
+        .await
+        .unwrap();
+
+    let swept = events::<SurplusSwept>(&logs);
+    assert_eq!(swept[0].beneficiary, None);
+    assert_eq!(swept[0].treasury, Some(harness.ata(&admin)));
+    assert_eq!(harness.token_amount(&admin).await, 500_000);
+    assert_eq!(harness.token_amount(&vault()).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_reconcile_reports_surplus() {
+    let (mut harness, user) = setup().await;
+    let reconcile_ix = |harness: &Harness| {
+        harness.ix(
+            crate::accounts::Reconcile {
+                vault: vault(),
+                vault_token_account: harness.ata(&vault()),
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::Reconcile {},
+        )
+    };
+
+    let logs = harness.send(&[reconcile_ix(&harness)], &[]).await.unwrap();
+    let reconciled = &events::<VaultReconciled>(&logs)[0];
+    assert_eq!(reconciled.vault_amount, 1_500_000);
+    assert_eq!(reconciled.liabilities, 1_000_000);
+    assert_eq!(reconciled.surplus, 500_000);
+    assert_eq!(reconciled.deficit, 0);
+
+    // once swept, the surplus is owed to the beneficiary and the books balance
+    let ixs = [
+        harness.sweep_ix(Some(&user.pubkey())),
+        reconcile_ix(&harness),
+    ];
+    let logs = harness.send(&ixs, &[]).await.unwrap();
+    let reconciled = &events::<VaultReconciled>(&logs)[0];
+    assert_eq!(reconciled.liabilities, 1_500_000);
+    assert_eq!(reconciled.surplus, 0);
+    assert_eq!(reconciled.deficit, 0);
+}
+
+#[tokio::test]
+async fn test_set_and_collect_fees() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let admin = harness.admin.pubkey();
+    let set_fees_ix = |harness: &Harness, deposit_fee_bps: u16| {
+        harness.ix(
+            crate::accounts::UpdateVaultConfig {
+                admin,
+                vault: vault(),
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::SetFees {
+                deposit_fee_bps,
+                withdraw_fee_bps: 0,
+            },
+        )
+    };
+
+    let ix = set_fees_ix(&harness, 10_001);
+    assert_error(harness.send(&[ix], &[]).await, ErrorCode::InvalidFee);
+    harness
+        .send(&[set_fees_ix(&harness, 100)], &[])
+        .await
+        .unwrap();
+
+    // a 1% deposit fee is kept back from the shares and owed to the treasury
+    let user = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_000_000).await;
+    harness.deposit(&user, 1_000_000, 0).await.unwrap();
+
+    let user_state: User = harness.account(&user_pda(&user.pubkey())).await;
+    assert_eq!(user_state.shares, 990_000);
+    let vault_state: Vault = harness.account(&vault()).await;
+    assert_eq!(vault_state.accrued_fees, 10_000);
+
+    harness.whitelist(&admin, *b"US").await;
+    harness.set_treasury(&admin).await;
+    let collect = harness.ix(
+        crate::accounts::CollectFees {
+            admin,
+            vault: vault(),
+            vault_token_account: harness.ata(&vault()),
+            treasury: harness.ata(&admin),
+            instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::CollectFees {},
+    );
+
+    // the approval only covers a transfer of the fees into the treasury
+    let (vault_ata, admin_ata, user_ata) = (
+        harness.ata(&vault()),
+        harness.ata(&admin),
+        harness.ata(&user.pubkey()),
+    );
+    let transfer = harness
+        .transfer_ix(&vault_ata, &user_ata, &admin, 10_000)
+        .await;
+    assert_error(
+        harness.send(&[collect.clone(), transfer], &[]).await,
+        ErrorCode::MissingTransferInstruction,
+    );
+    let transfer = harness
+        .transfer_ix(&vault_ata, &admin_ata, &admin, 10_001)
+        .await;
+    assert_error(
+        harness.send(&[collect.clone(), transfer], &[]).await,
+        anchor_lang::error::ErrorCode::RequireEqViolated,
+    );
+
+    let transfer = harness
+        .transfer_ix(&vault_ata, &admin_ata, &admin, 10_000)
+        .await;
+    let logs = harness
+        .send(&[collect.clone(), transfer], &[])
+        .await
+        .unwrap();
+
+    let collected = &events::<FeesCollected>(&logs)[0];
+    assert_eq!((collected.treasury, collected.amount), (admin_ata, 10_000));
+    assert_eq!(harness.token_amount(&admin).await, 10_000);
+    assert_eq!(harness.token_amount(&vault()).await, 990_000);
+
+    let vault_state: Vault = harness.account(&vault()).await;
+    assert_eq!(vault_state.accrued_fees, 0);
+    assert_eq!(vault_state.liabilities(), Some(990_000));
+
+    let transfer = harness.transfer_ix(&vault_ata, &admin_ata, &admin, 1).await;
+    assert_error(
+        harness.send(&[collect, transfer], &[]).await,
+        ErrorCode::NoFees,
+    );
+}
@@ -0,0 +1,158 @@
+use {solana_instruction::AccountMeta, solana_signer::Signer};
+
+use super::harness::*;
+use crate::{
+    error::ErrorCode,
+    events::{JurisdictionUpdated, UserRemoved},
+    state::{JurisdictionPair, PolicyRules, User, Vault},
+    MintExtensions,
+};
+
+#[tokio::test]
+async fn test_remove_suspends_user_with_shares() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let user = harness.new_user(*b"US").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_500_000).await;
+    harness.mint_to(&receiver.pubkey(), 1_000).await;
+    harness.deposit(&user, 1_000_000, 0).await.unwrap();
+    let vault_state: Vault = harness.account(&vault()).await;
+    let user_count = vault_state.user_count;
+
+    let ix = harness.remove_ix(&user.pubkey(), Some(harness.ata(&user.pubkey())));
+    let logs = harness.send(&[ix], &[]).await.unwrap();
+    assert!(events::<UserRemoved>(&logs)[0].suspended);
+
+    // the entry and its shares stay, but the wallet is frozen and no longer whitelisted
+    let entry: User = harness.account(&user_pda(&user.pubkey())).await;
+    assert!(entry.suspended);
+    assert_eq!(entry.shares, 1_000_000);
+    assert!(harness.is_frozen(&harness.ata(&user.pubkey())).await);
+
+    let vault_state: Vault = harness.account(&vault()).await;
+    assert_eq!(vault_state.user_count, user_count - 1);
+
+    // a suspended wallet is rejected by the hook even from an account that was not frozen
+    let user_account = harness.create_token_account(&user.pubkey()).await;
+    let receiver_ata = harness.ata(&receiver.pubkey());
+    let ix = harness
+        .transfer_ix(&receiver_ata, &user_account, &receiver.pubkey(), 1)
+        .await;
+    assert_error(
+        harness.send(&[ix], &[&receiver]).await,
+        ErrorCode::NotWhitelisted,
+    );
+
+    harness
+        .send(&[harness.reinstate_ix(&user.pubkey())], &[])
+        .await
+        .unwrap();
+
+    let entry: User = harness.account(&user_pda(&user.pubkey())).await;
+    assert!(!entry.suspended);
+    let vault_state: Vault = harness.account(&vault()).await;
+    assert_eq!(vault_state.user_count, user_count);
+    assert!(!harness.is_frozen(&harness.ata(&user.pubkey())).await);
+    harness
+        .transfer(&user, &receiver.pubkey(), 500_000)
+        .await
+        .unwrap();
+
+    let ix = harness.reinstate_ix(&user.pubkey());
+    assert_error(harness.send(&[ix], &[]).await, ErrorCode::UserNotSuspended);
+
+    // a wallet without deposits is removed and its token account frozen
+    let idle = harness.new_user(*b"US").await;
+    harness.mint_to(&idle.pubkey(), 1_000).await;
+
+    let ix = harness.remove_ix(&idle.pubkey(), Some(harness.ata(&idle.pubkey())));
+    let logs = harness.send(&[ix], &[]).await.unwrap();
+    assert!(!events::<UserRemoved>(&logs)[0].suspended);
+
+    assert!(harness
+        .account_data(&user_pda(&idle.pubkey()))
+        .await
+        .is_none());
+    assert!(harness.is_frozen(&harness.ata(&idle.pubkey())).await);
+}
+
+#[tokio::test]
+async fn test_remove_freezes_other_token_accounts() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let user = harness.new_user(*b"US").await;
+    let other = harness.new_user(*b"US").await;
+
+    // the wallet never opened its associated account, only a plain token account
+    let user_account = harness.create_token_account(&user.pubkey()).await;
+    let other_account = harness.create_token_account(&other.pubkey()).await;
+
+    let mut ix = harness.remove_ix(&user.pubkey(), None);
+    ix.accounts.push(AccountMeta::new(other_account, false));
+    assert_error(
+        harness.send(&[ix.clone()], &[]).await,
+        ErrorCode::ForeignTokenAccount,
+    );
+
+    ix.accounts.pop();
+    ix.accounts.push(AccountMeta::new(user_account, false));
+    harness.send(&[ix], &[]).await.unwrap();
+
+    assert!(harness.is_frozen(&user_account).await);
+    assert!(!harness.is_frozen(&other_account).await);
+}
+
+#[tokio::test]
+async fn test_set_jurisdiction() {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+    let wallet = harness.new_user(*b"DE").await;
+    let receiver = harness.new_user(*b"US").await;
+    harness.mint_to(&wallet.pubkey(), 1_000).await;
+
+    harness
+        .set_policy(PolicyRules {
+            check_jurisdictions: true,
+            allowed_pairs: vec![JurisdictionPair {
+                from: *b"US",
+                to: *b"US",
+            }],
+            ..default_rules()
+        })
+        .await;
+    assert_error(
+        harness.transfer(&wallet, &receiver.pubkey(), 100).await,
+        ErrorCode::JurisdictionNotAllowed,
+    );
+
+    let set_jurisdiction_ix = |harness: &Harness, jurisdiction| {
+        harness.ix(
+            crate::accounts::SetJurisdiction {
+                admin: harness.admin.pubkey(),
+                vault: vault(),
+                user: user_pda(&wallet.pubkey()),
+                event_authority: event_authority(),
+                program: PROGRAM_ID,
+            },
+            crate::instruction::SetJurisdiction {
+                user: wallet.pubkey(),
+                jurisdiction,
+            },
+        )
+    };
+
+    let ix = set_jurisdiction_ix(&harness, *b"us");
+    assert_error(
+        harness.send(&[ix], &[]).await,
+        ErrorCode::InvalidJurisdiction,
+    );
+
+    let ix = set_jurisdiction_ix(&harness, *b"US");
+    let logs = harness.send(&[ix], &[]).await.unwrap();
+    assert_eq!(events::<JurisdictionUpdated>(&logs)[0].jurisdiction, *b"US");
+
+    let entry: User = harness.account(&user_pda(&wallet.pubkey())).await;
+    assert_eq!(entry.jurisdiction, *b"US");
+    harness
+        .transfer(&wallet, &receiver.pubkey(), 100)
+        .await
+        .unwrap();
+}
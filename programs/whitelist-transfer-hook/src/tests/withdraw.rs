@@ -0,0 +1,237 @@
+use {
+    solana_instruction::{AccountMeta, Instruction},
+    solana_keypair::Keypair,
+    solana_pubkey::Pubkey,
+    solana_signer::Signer,
+};
+
+use super::harness::*;
+use crate::{
+    error::ErrorCode,
+    events::{WithdrawProcessed, Withdrawn, YieldDistributed},
+    state::{User, WithdrawRequest},
+    MintExtensions,
+};
+
+const COOLDOWN: i64 = 3_600;
+
+fn request_pda(user: &Pubkey, index: u64) -> Pubkey {
+    pda(&[b"withdraw-request", user.as_ref(), &index.to_le_bytes()])
+}
+
+fn request_ix(harness: &Harness, user: &Pubkey, amount: u64, index: u64) -> Instruction {
+    harness.ix(
+        crate::accounts::RequestWithdraw {
+            user: *user,
+            vault: vault(),
+            user_account: user_pda(user),
+            withdraw_request: request_pda(user, index),
+            system_program: solana_sdk_ids::system_program::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::RequestWithdraw { amount, index },
+    )
+}
+
+fn crank_ix(harness: &Harness, requests: &[Pubkey]) -> Instruction {
+    let mut ix = harness.ix(
+        crate::accounts::ProcessWithdrawals {
+            vault: vault(),
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::ProcessWithdrawals {},
+    );
+    ix.accounts.extend(
+        requests
+            .iter()
+            .map(|request| AccountMeta::new(*request, false)),
+    );
+
+    ix
+}
+
+/// The claim followed by the transfer out of the vault it approves
+async fn claim_ixs(
+    harness: &mut Harness,
+    user: &Pubkey,
+    index: u64,
+    amount: u64,
+) -> Vec<Instruction> {
+    let claim = harness.ix(
+        crate::accounts::ClaimWithdraw {
+            user: *user,
+            vault: vault(),
+            withdraw_request: request_pda(user, index),
+            vault_token_account: harness.ata(&vault()),
+            token_program: TOKEN_PROGRAM_ID,
+            instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::ClaimWithdraw { index },
+    );
+    let transfer = harness
+        .transfer_ix(&harness.ata(&vault()), &harness.ata(user), user, amount)
+        .await;
+
+    vec![claim, transfer]
+}
+
+async fn setup() -> (Harness, Keypair) {
+    let mut harness = Harness::new(MintExtensions::default()).await;
+
+    let ix = harness.ix(
+        crate::accounts::UpdateVaultConfig {
+            admin: harness.admin.pubkey(),
+            vault: vault(),
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::SetWithdrawCooldown {
+            withdraw_cooldown: COOLDOWN,
+        },
+    );
+    harness.send(&[ix], &[]).await.unwrap();
+
+    let user = harness.new_user(*b"US").await;
+    harness.mint_to(&user.pubkey(), 1_000_000).await;
+    harness.deposit(&user, 1_000_000, 0).await.unwrap();
+
+    (harness, user)
+}
+
+#[tokio::test]
+async fn test_concurrent_requests_and_crank() {
+    let (mut harness, user) = setup().await;
+    let wallet = user.pubkey();
+    let requests = [request_pda(&wallet, 0), request_pda(&wallet, 1)];
+
+    let ixs = [
+        request_ix(&harness, &wallet, 400_000, 0),
+        request_ix(&harness, &wallet, 300_000, 1),
+    ];
+    harness.send(&ixs, &[&user]).await.unwrap();
+
+    // the cooldown holds back both the claim and the crank
+    let ixs = claim_ixs(&mut harness, &wallet, 0, 400_000).await;
+    assert_error(
+        harness.send(&ixs, &[&user]).await,
+        ErrorCode::WithdrawNotMatured,
+    );
+
+    let logs = harness
+        .send(&[crank_ix(&harness, &requests)], &[])
+        .await
+        .unwrap();
+    assert!(events::<WithdrawProcessed>(&logs).is_empty());
+    let request: WithdrawRequest = harness.account(&requests[0]).await;
+    assert!(!request.processed);
+
+    let now = harness.clock().await.unix_timestamp;
+    harness.set_clock(now + COOLDOWN).await;
+
+    // passed in a different order, an identical transaction would be deduplicated
+    let logs = harness
+        .send(&[crank_ix(&harness, &[requests[1], requests[0]])], &[])
+        .await
+        .unwrap();
+    let processed = events::<WithdrawProcessed>(&logs);
+    assert_eq!(processed.len(), 2);
+    assert_eq!((processed[0].index, processed[0].payout), (1, 300_000));
+    assert_eq!((processed[1].index, processed[1].payout), (0, 400_000));
+
+    // processed requests are skipped by later cranks
+    let logs = harness
+        .send(&[crank_ix(&harness, &requests[..1])], &[])
+        .await
+        .unwrap();
+    assert!(events::<WithdrawProcessed>(&logs).is_empty());
+
+    for (index, amount) in [(0, 400_000), (1, 300_000)] {
+        let ixs = claim_ixs(&mut harness, &wallet, index, amount).await;
+        let logs = harness.send(&ixs, &[&user]).await.unwrap();
+
+        let withdrawn = events::<Withdrawn>(&logs);
+        assert_eq!((withdrawn[0].index, withdrawn[0].amount), (index, amount));
+        assert!(harness
+            .account_data(&request_pda(&wallet, index))
+            .await
+            .is_none());
+    }
+
+    assert_eq!(harness.token_amount(&wallet).await, 700_000);
+    let vault: crate::state::Vault = harness.account(&vault()).await;
+    assert_eq!(vault.total_outstanding, 300_000);
+    assert_eq!(vault.total_claimable, 0);
+}
+
+#[tokio::test]
+async fn test_yield_is_shared_pro_rata_and_claimable() {
+    let (mut harness, first) = setup().await;
+    let second = harness.new_user(*b"US").await;
+    harness.mint_to(&second.pubkey(), 3_000_000).await;
+    harness.deposit(&second, 3_000_000, 0).await.unwrap();
+
+    let admin = harness.admin.pubkey();
+    harness.whitelist(&admin, *b"US").await;
+    harness.mint_to(&admin, 400_000).await;
+
+    let admin_ata = harness.ata(&admin);
+    let vault_ata = harness.ata(&vault());
+    let transfer = harness
+        .transfer_ix(&admin_ata, &vault_ata, &admin, 400_000)
+        .await;
+    let distribute = harness.ix(
+        crate::accounts::DistributeYield {
+            admin,
+            vault: vault(),
+            mint: harness.mint,
+            vault_token_account: vault_ata,
+            instructions: anchor_lang::solana_program::sysvar::instructions::ID,
+            event_authority: event_authority(),
+            program: PROGRAM_ID,
+        },
+        crate::instruction::DistributeYield {},
+    );
+    let logs = harness.send(&[transfer, distribute], &[]).await.unwrap();
+
+    let distributed = &events::<YieldDistributed>(&logs)[0];
+    assert_eq!(distributed.amount, 400_000);
+    assert_eq!(distributed.total_outstanding, 4_400_000);
+
+    // each share is now worth 1.1 tokens, so both depositors earn 10% on their deposit
+    let vault_state: crate::state::Vault = harness.account(&vault()).await;
+    for (user, value) in [(&first, 1_100_000), (&second, 3_300_000)] {
+        let user_state: User = harness.account(&user_pda(&user.pubkey())).await;
+        assert_eq!(
+            vault_state.assets_for_shares(user_state.shares),
+            Some(value)
+        );
+    }
+
+    let ixs = [
+        request_ix(&harness, &first.pubkey(), 1_100_000, 0),
+        request_ix(&harness, &second.pubkey(), 3_300_000, 0),
+    ];
+    harness.send(&ixs, &[&first, &second]).await.unwrap();
+
+    let now = harness.clock().await.unix_timestamp;
+    harness.set_clock(now + COOLDOWN).await;
+
+    for (user, amount) in [(&first, 1_100_000), (&second, 3_300_000)] {
+        let ixs = claim_ixs(&mut harness, &user.pubkey(), 0, amount).await;
+        let logs = harness.send(&ixs, &[user]).await.unwrap();
+
+        assert_eq!(events::<Withdrawn>(&logs)[0].amount, amount);
+        assert_eq!(harness.token_amount(&user.pubkey()).await, amount);
+        let user_state: User = harness.account(&user_pda(&user.pubkey())).await;
+        assert_eq!(user_state.shares, 0);
+    }
+
+    let vault_state: crate::state::Vault = harness.account(&vault()).await;
+    assert_eq!(vault_state.total_outstanding, 0);
+    assert_eq!(vault_state.total_shares, 0);
+    assert_eq!(harness.token_amount(&vault()).await, 0);
+}